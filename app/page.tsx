@@ -5,6 +5,7 @@ import { toast } from "sonner";
 import { SidebarProvider, SidebarInset } from "@/components/ui/sidebar";
 import { AppSidebar } from "@/components/app-sidebar";
 import { ProxyForm } from "@/components/proxy-form";
+import { LogConsole } from "@/components/log-console";
 import { Card, CardContent, CardHeader, CardTitle } from "@/components/ui/card";
 import { Button } from "@/components/ui/button";
 import { Badge } from "@/components/ui/badge";
@@ -381,11 +382,11 @@ export default function HomePage() {
                     </div>
 
                     <div className="flex items-center justify-between">
-                      <span className="text-muted-foreground">SOCKS5 代理</span>
-                      {selectedConfig.socks5_proxy ? (
+                      <span className="text-muted-foreground">上游代理</span>
+                      {selectedConfig.upstream_proxy ? (
                         <div className="flex items-center gap-2 font-mono text-sm p-1 bg-muted rounded-md">
                           <Network className="w-4 h-4" />
-                          <span>{selectedConfig.socks5_proxy}</span>
+                          <span>{selectedConfig.upstream_proxy}</span>
                         </div>
                       ) : (
                         <Badge variant="outline">
@@ -411,6 +412,8 @@ export default function HomePage() {
                     )}
                   </CardContent>
                 </Card>
+
+                <LogConsole />
               </div>
             ) : (
               <div className="flex flex-col items-center justify-center h-full text-center">
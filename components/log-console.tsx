@@ -0,0 +1,61 @@
+"use client";
+
+import { useEffect, useRef, useState } from "react";
+import { listen } from "@tauri-apps/api/event";
+import { Card, CardContent, CardHeader, CardTitle } from "@/components/ui/card";
+import { Button } from "@/components/ui/button";
+import type { LogEvent } from "@/types/proxy";
+
+// 最多保留的日志条数
+const MAX_LOG_LINES = 200;
+
+const levelClass: Record<string, string> = {
+  ERROR: "text-red-500",
+  WARN: "text-yellow-500",
+  INFO: "text-muted-foreground",
+};
+
+export function LogConsole() {
+  const [logs, setLogs] = useState<LogEvent[]>([]);
+  const bottomRef = useRef<HTMLDivElement | null>(null);
+
+  useEffect(() => {
+    const unlisten = listen<LogEvent>("app-log", (event) => {
+      setLogs((prev) => [...prev, event.payload].slice(-MAX_LOG_LINES));
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  useEffect(() => {
+    bottomRef.current?.scrollIntoView({ block: "nearest" });
+  }, [logs]);
+
+  return (
+    <Card>
+      <CardHeader className="flex flex-row items-center justify-between">
+        <CardTitle>运行日志</CardTitle>
+        <Button variant="outline" size="sm" onClick={() => setLogs([])}>
+          清空
+        </Button>
+      </CardHeader>
+      <CardContent>
+        <div className="h-64 overflow-auto rounded-md border bg-muted/50 p-2 font-mono text-xs">
+          {logs.length === 0 ? (
+            <p className="text-muted-foreground">暂无日志</p>
+          ) : (
+            logs.map((log, index) => (
+              <div key={index} className="whitespace-pre-wrap break-all">
+                <span className="text-muted-foreground">{new Date(log.timestamp).toLocaleTimeString()}</span>{" "}
+                <span className={levelClass[log.level] ?? ""}>{log.level}</span>{" "}
+                <span className="text-muted-foreground">{log.target}</span> {log.message}
+              </div>
+            ))
+          )}
+          <div ref={bottomRef} />
+        </div>
+      </CardContent>
+    </Card>
+  );
+}
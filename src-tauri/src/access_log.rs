@@ -0,0 +1,62 @@
+use log::error;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use tokio::io::AsyncWriteExt;
+
+/// 从文件末尾反向读取时每次读取的块大小
+const TAIL_CHUNK_SIZE: u64 = 8 * 1024;
+
+/// 追加一行访问日志
+pub async fn append_line(path: &str, line: &str) {
+    let result = async {
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await?;
+        file.write_all(format!("{line}\n").as_bytes()).await
+    }
+    .await;
+
+    if let Err(e) = result {
+        error!("Failed to write access log {path}: {e}");
+    }
+}
+
+/// 读取文件最后 N 行（从末尾向前按块读取，不读取整个文件）
+/// 返回结果按时间顺序排列（最新的在最后）
+pub fn tail_lines(path: &Path, lines: usize) -> std::io::Result<Vec<String>> {
+    if lines == 0 {
+        return Ok(Vec::new());
+    }
+
+    let mut file = std::fs::File::open(path)?;
+    let file_len = file.metadata()?.len();
+
+    let mut buffer: Vec<u8> = Vec::new();
+    let mut pos = file_len;
+
+    // 多读一个换行符，以便丢弃最前面的不完整行
+    while pos > 0 && buffer.iter().filter(|&&b| b == b'\n').count() <= lines {
+        let read_size = TAIL_CHUNK_SIZE.min(pos);
+        pos -= read_size;
+        file.seek(SeekFrom::Start(pos))?;
+
+        let mut chunk = vec![0u8; read_size as usize];
+        file.read_exact(&mut chunk)?;
+        chunk.extend_from_slice(&buffer);
+        buffer = chunk;
+    }
+
+    let text = String::from_utf8_lossy(&buffer);
+    let all: Vec<&str> = text.lines().collect();
+    // 未读到文件开头时，第一行可能不完整
+    let all = if pos > 0 && !all.is_empty() {
+        &all[1..]
+    } else {
+        &all[..]
+    };
+    let start = all.len().saturating_sub(lines);
+
+    Ok(all[start..].iter().map(|line| line.to_string()).collect())
+}
@@ -0,0 +1,110 @@
+use futures_util::StreamExt;
+use serde::Serialize;
+
+/// 单次压测允许的最大请求数，避免压垮本机
+pub const MAX_BENCH_REQUESTS: u32 = 10_000;
+
+/// 单次压测允许的最大并发数
+pub const MAX_BENCH_CONCURRENCY: u32 = 256;
+
+/// 单个请求的超时时间（秒）
+const BENCH_REQUEST_TIMEOUT_SECONDS: u64 = 30;
+
+/// 压测结果（延迟单位为毫秒）
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchResult {
+    pub url: String,
+    pub requests: u32,
+    pub concurrency: u32,
+    pub successful: u32,
+    /// 连接失败、超时或返回 5xx 的请求数
+    pub failed: u32,
+    pub error_rate: f64,
+    pub duration_ms: u64,
+    pub requests_per_second: f64,
+    pub latency_mean_ms: f64,
+    pub latency_p50_ms: f64,
+    pub latency_p90_ms: f64,
+    pub latency_p99_ms: f64,
+    pub latency_max_ms: f64,
+}
+
+/// 校验压测参数
+pub fn validate(requests: u32, concurrency: u32) -> Result<(), String> {
+    if requests == 0 || requests > MAX_BENCH_REQUESTS {
+        return Err(format!(
+            "requests must be between 1 and {MAX_BENCH_REQUESTS}"
+        ));
+    }
+    if concurrency == 0 || concurrency > MAX_BENCH_CONCURRENCY {
+        return Err(format!(
+            "concurrency must be between 1 and {MAX_BENCH_CONCURRENCY}"
+        ));
+    }
+    Ok(())
+}
+
+/// 以指定并发向 url 发送 requests 个 GET 请求并统计结果
+/// 目标为本机监听地址，不走系统代理，并接受自签名证书
+pub async fn run(url: &str, requests: u32, concurrency: u32) -> Result<BenchResult, String> {
+    validate(requests, concurrency)?;
+
+    let client = reqwest::Client::builder()
+        .no_proxy()
+        .danger_accept_invalid_certs(true)
+        .redirect(reqwest::redirect::Policy::none())
+        .timeout(std::time::Duration::from_secs(
+            BENCH_REQUEST_TIMEOUT_SECONDS,
+        ))
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {e}"))?;
+
+    let started = std::time::Instant::now();
+    let outcomes: Vec<(bool, f64)> = futures_util::stream::iter(0..requests)
+        .map(|_| {
+            let client = client.clone();
+            async move {
+                let request_started = std::time::Instant::now();
+                let ok = match client.get(url).send().await {
+                    // 读取完整响应体，使延迟包含传输时间
+                    Ok(res) => !res.status().is_server_error() && res.bytes().await.is_ok(),
+                    Err(_) => false,
+                };
+                (ok, request_started.elapsed().as_secs_f64() * 1000.0)
+            }
+        })
+        .buffer_unordered(concurrency as usize)
+        .collect()
+        .await;
+    let elapsed = started.elapsed();
+
+    let successful = outcomes.iter().filter(|(ok, _)| *ok).count() as u32;
+    let failed = requests - successful;
+    let mut latencies: Vec<f64> = outcomes.into_iter().map(|(_, ms)| ms).collect();
+    latencies.sort_by(|a, b| a.total_cmp(b));
+
+    Ok(BenchResult {
+        url: url.to_string(),
+        requests,
+        concurrency,
+        successful,
+        failed,
+        error_rate: f64::from(failed) / f64::from(requests),
+        duration_ms: elapsed.as_millis() as u64,
+        requests_per_second: f64::from(requests) / elapsed.as_secs_f64().max(f64::EPSILON),
+        latency_mean_ms: latencies.iter().sum::<f64>() / latencies.len() as f64,
+        latency_p50_ms: percentile(&latencies, 0.50),
+        latency_p90_ms: percentile(&latencies, 0.90),
+        latency_p99_ms: percentile(&latencies, 0.99),
+        latency_max_ms: latencies.last().copied().unwrap_or_default(),
+    })
+}
+
+/// 已排序数据的百分位数（最近秩法）
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = (p * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
@@ -0,0 +1,116 @@
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+/// 默认保留的最近请求数
+pub const DEFAULT_CAPTURE_BUFFER_SIZE: usize = 100;
+
+/// 一次请求/响应的元数据（不包含请求体和响应体）
+#[derive(Debug, Clone)]
+pub struct CapturedExchange {
+    pub started_at: chrono::DateTime<chrono::Local>,
+    pub client_ip: String,
+    pub method: String,
+    pub url: String,
+    pub http_version: String,
+    pub request_headers: Vec<(String, String)>,
+    pub status: u16,
+    pub response_headers: Vec<(String, String)>,
+    pub duration_ms: u64,
+}
+
+/// 最近请求的环形缓冲区，超过容量时丢弃最旧的记录
+#[derive(Debug, Default)]
+pub struct RequestCapture {
+    entries: Mutex<VecDeque<CapturedExchange>>,
+}
+
+impl RequestCapture {
+    pub fn push(&self, exchange: CapturedExchange, capacity: usize) {
+        let mut entries = self.entries.lock().unwrap();
+        while entries.len() >= capacity.max(1) {
+            entries.pop_front();
+        }
+        entries.push_back(exchange);
+    }
+
+    /// 按 HAR 1.2 格式导出
+    pub fn to_har(&self) -> Value {
+        let entries = self.entries.lock().unwrap();
+        let entries: Vec<Value> = entries.iter().map(har_entry).collect();
+
+        json!({
+            "log": {
+                "version": "1.2",
+                "creator": {
+                    "name": "ReverseProxyGUI",
+                    "version": env!("CARGO_PKG_VERSION"),
+                },
+                "pages": [],
+                "entries": entries,
+            }
+        })
+    }
+}
+
+fn har_headers(headers: &[(String, String)]) -> Vec<Value> {
+    headers
+        .iter()
+        .map(|(name, value)| json!({ "name": name, "value": value }))
+        .collect()
+}
+
+fn har_entry(exchange: &CapturedExchange) -> Value {
+    let query: Vec<Value> = url::Url::parse(&exchange.url)
+        .map(|url| {
+            url.query_pairs()
+                .map(|(name, value)| json!({ "name": name, "value": value }))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mime_type = exchange
+        .response_headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-type"))
+        .map(|(_, value)| value.as_str())
+        .unwrap_or("");
+
+    // 未采集请求体/响应体，大小按 HAR 约定记为 -1
+    json!({
+        "startedDateTime": exchange.started_at.to_rfc3339(),
+        "time": exchange.duration_ms,
+        "serverIPAddress": "",
+        "request": {
+            "method": exchange.method,
+            "url": exchange.url,
+            "httpVersion": exchange.http_version,
+            "cookies": [],
+            "headers": har_headers(&exchange.request_headers),
+            "queryString": query,
+            "headersSize": -1,
+            "bodySize": -1,
+        },
+        "response": {
+            "status": exchange.status,
+            "statusText": axum::http::StatusCode::from_u16(exchange.status)
+                .ok()
+                .and_then(|s| s.canonical_reason())
+                .unwrap_or(""),
+            "httpVersion": exchange.http_version,
+            "cookies": [],
+            "headers": har_headers(&exchange.response_headers),
+            "content": { "size": -1, "mimeType": mime_type },
+            "redirectURL": "",
+            "headersSize": -1,
+            "bodySize": -1,
+        },
+        "cache": {},
+        "timings": {
+            "send": 0,
+            "wait": exchange.duration_ms,
+            "receive": 0,
+        },
+        "comment": format!("client {}", exchange.client_ip),
+    })
+}
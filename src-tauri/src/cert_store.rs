@@ -0,0 +1,187 @@
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::proxy_manager::{generate_self_signed_cert, ProxyConfig, ProxyError};
+
+/// 持久化自签名证书的目录（应用数据目录下的 certs）
+static CERT_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// 生成证书时使用的参数，用于判断缓存的证书是否需要重新生成
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct CertParams {
+    common_name: Option<String>,
+    sans: Vec<String>,
+}
+
+impl CertParams {
+    fn from_config(config: &ProxyConfig) -> Self {
+        Self {
+            common_name: config
+                .cert_common_name
+                .as_deref()
+                .map(str::trim)
+                .filter(|cn| !cn.is_empty())
+                .map(str::to_string),
+            sans: config
+                .cert_sans
+                .iter()
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect(),
+        }
+    }
+}
+
+/// 设置证书目录（应用启动时调用一次）
+pub fn init(dir: PathBuf) {
+    if let Err(e) = std::fs::create_dir_all(&dir) {
+        warn!("Failed to create cert directory {}: {e}", dir.display());
+    }
+    let _ = CERT_DIR.set(dir);
+}
+
+fn cert_dir() -> PathBuf {
+    CERT_DIR
+        .get()
+        .cloned()
+        .unwrap_or_else(|| std::env::temp_dir().join("reverse-proxy-gui-certs"))
+}
+
+/// 配置对应的证书、私钥和参数文件路径
+pub fn cert_paths(config_id: &str) -> (PathBuf, PathBuf, PathBuf) {
+    let dir = cert_dir();
+    (
+        dir.join(format!("{config_id}_cert.pem")),
+        dir.join(format!("{config_id}_key.pem")),
+        dir.join(format!("{config_id}_params.json")),
+    )
+}
+
+/// 获取配置的自签名证书文件路径，缓存不存在或参数变化时重新生成
+pub fn load_or_generate(config: &ProxyConfig) -> Result<(PathBuf, PathBuf), ProxyError> {
+    let (cert_path, key_path, params_path) = cert_paths(&config.id);
+    let params = CertParams::from_config(config);
+
+    let cached_params = std::fs::read(&params_path)
+        .ok()
+        .and_then(|data| serde_json::from_slice::<CertParams>(&data).ok());
+
+    if cert_path.is_file() && key_path.is_file() && cached_params.as_ref() == Some(&params) {
+        info!("Reusing cached certificate for config {}", config.name);
+        return Ok((cert_path, key_path));
+    }
+
+    regenerate(config)
+}
+
+/// 读取配置已缓存的证书 PEM，不会生成新证书（尚未生成时返回错误）
+pub fn export_cert_pem(config: &ProxyConfig) -> Result<String, ProxyError> {
+    let (cert_path, _, _) = cert_paths(&config.id);
+    if !cert_path.is_file() {
+        return Err(ProxyError::CertificateError(format!(
+            "No certificate generated yet for config {}; start the proxy first",
+            config.name
+        )));
+    }
+    std::fs::read_to_string(&cert_path)
+        .map_err(|e| ProxyError::CertificateError(format!("Failed to read cert file: {e}")))
+}
+
+/// 强制为配置重新生成自签名证书
+pub fn regenerate(config: &ProxyConfig) -> Result<(PathBuf, PathBuf), ProxyError> {
+    let (cert_path, key_path, params_path) = cert_paths(&config.id);
+    let params = CertParams::from_config(config);
+
+    let (cert_pem, key_pem) =
+        generate_self_signed_cert(params.common_name.as_deref(), &params.sans)?;
+
+    std::fs::create_dir_all(cert_dir())
+        .map_err(|e| ProxyError::CertificateError(format!("Failed to create cert dir: {e}")))?;
+    std::fs::write(&cert_path, cert_pem)
+        .map_err(|e| ProxyError::CertificateError(format!("Failed to write cert file: {e}")))?;
+    write_private_key(&key_path, &key_pem)
+        .map_err(|e| ProxyError::CertificateError(format!("Failed to write key file: {e}")))?;
+
+    let params_json = serde_json::to_vec(&params)
+        .map_err(|e| ProxyError::CertificateError(format!("Failed to serialize params: {e}")))?;
+    std::fs::write(&params_path, params_json)
+        .map_err(|e| ProxyError::CertificateError(format!("Failed to write params file: {e}")))?;
+
+    info!("Generated new certificate for config {}", config.name);
+    Ok((cert_path, key_path))
+}
+
+/// 写入私钥文件，unix 下仅所有者可读写（0600）
+fn write_private_key(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::io::Write;
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+
+        let mut file = std::fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(path)?;
+        // mode 仅在新建文件时生效，旧版本遗留的文件需要显式收紧权限
+        file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+        file.write_all(contents)
+    }
+    #[cfg(not(unix))]
+    {
+        std::fs::write(path, contents)
+    }
+}
+
+/// 删除配置缓存的证书文件
+pub fn remove(config_id: &str) {
+    let (cert_path, key_path, params_path) = cert_paths(config_id);
+    let _ = std::fs::remove_file(cert_path);
+    let _ = std::fs::remove_file(key_path);
+    let _ = std::fs::remove_file(params_path);
+}
+
+/// 清理不属于 keep_ids 中任何配置的证书文件，返回删除的文件数
+/// 同时扫描证书目录和旧版本使用的系统临时目录（崩溃后可能遗留 `<id>_cert.pem` 等文件）
+pub fn cleanup_orphaned(keep_ids: &HashSet<String>) -> usize {
+    let mut removed = remove_orphans_in(&cert_dir(), keep_ids);
+    removed += remove_orphans_in(&std::env::temp_dir(), keep_ids);
+    if removed > 0 {
+        info!("Removed {removed} orphaned certificate files");
+    }
+    removed
+}
+
+fn remove_orphans_in(dir: &Path, keep_ids: &HashSet<String>) -> usize {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return 0;
+    };
+
+    let mut removed = 0;
+    for entry in entries.flatten() {
+        let file_name = entry.file_name();
+        let Some(file_name) = file_name.to_str() else {
+            continue;
+        };
+        let Some(id) = ["_cert.pem", "_key.pem", "_params.json"]
+            .iter()
+            .find_map(|suffix| file_name.strip_suffix(suffix))
+        else {
+            continue;
+        };
+
+        // 仅处理以配置 ID（UUID）命名的文件，避免误删其他程序的文件
+        if uuid::Uuid::parse_str(id).is_err() || keep_ids.contains(id) {
+            continue;
+        }
+        match std::fs::remove_file(entry.path()) {
+            Ok(()) => removed += 1,
+            Err(e) => warn!("Failed to remove {}: {e}", entry.path().display()),
+        }
+    }
+    removed
+}
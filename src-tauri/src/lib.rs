@@ -1,15 +1,27 @@
 use log::{error, info, warn};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tauri::{Manager, State};
 use tauri_plugin_store::StoreExt;
-use tokio::sync::RwLock;
+use tokio::sync::{Mutex, RwLock};
 
+mod access_log;
+mod benchmark;
+mod capture;
+mod cert_store;
+mod log_forwarder;
+mod metrics;
 mod proxy_manager;
+mod share_link;
+mod store_health;
+mod transform;
+mod websocket;
 use proxy_manager::*;
 
 pub struct AppState {
     pub proxy_manager: ProxyManager,
+    /// 串行化对存储中 proxy_configs 的读-改-写，避免并发命令互相覆盖；只读命令无需加锁
+    pub config_lock: Arc<Mutex<()>>,
 }
 
 // 定义统一的错误响应格式
@@ -22,8 +34,11 @@ struct ErrorResponse {
 impl From<String> for ErrorResponse {
     fn from(error: String) -> Self {
         // 尝试从错误消息中提取错误代码
+        // start_all、自动重启等路径以字符串形式传递 ProxyError::PortInUse
         let code = if error.contains("Port") && error.contains("in use") {
             "PORT_IN_USE"
+        } else if error.contains("Restart required") {
+            "RESTART_REQUIRED"
         } else if error.contains("not found") {
             "NOT_FOUND"
         } else if error.contains("Failed to deserialize") {
@@ -39,8 +54,20 @@ impl From<String> for ErrorResponse {
     }
 }
 
+impl From<ProxyError> for ErrorResponse {
+    fn from(error: ProxyError) -> Self {
+        ErrorResponse {
+            code: error.code().to_string(),
+            error: error.to_string(),
+        }
+    }
+}
+
 #[tauri::command]
-async fn get_all_configs(app: tauri::AppHandle) -> Result<Vec<ProxyConfig>, String> {
+async fn get_all_configs(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<Vec<ProxyConfig>, String> {
     let store = app.store("store.json").map_err(|e| {
         error!("Failed to open store: {e}");
         format!("Failed to open store: {e}")
@@ -50,14 +77,22 @@ async fn get_all_configs(app: tauri::AppHandle) -> Result<Vec<ProxyConfig>, Stri
         Some(value) => {
             // 验证并反序列化配置
             match serde_json::from_value::<Vec<ProxyConfig>>(value.clone()) {
-                Ok(configs) => {
+                Ok(mut configs) => {
                     info!("Successfully loaded {} configs", configs.len());
+                    // 稳定排序：order 相同时保持存储中的顺序
+                    configs.sort_by_key(|c| c.order);
                     Ok(configs)
                 }
                 Err(e) => {
                     error!("Failed to deserialize configs: {e}");
-                    // 如果配置损坏，返回空列表而不是错误
+                    // 如果配置损坏，备份原始数据后重置为空列表，返回空列表而不是错误
                     warn!("Returning empty config list due to deserialization error");
+                    let _guard = state.config_lock.lock().await;
+                    backup_corrupt_configs(&store, value);
+                    store.set("proxy_configs", serde_json::json!([]));
+                    store
+                        .save()
+                        .map_err(|e| format!("Failed to save store: {e}"))?;
                     Ok(Vec::new())
                 }
             }
@@ -69,26 +104,163 @@ async fn get_all_configs(app: tauri::AppHandle) -> Result<Vec<ProxyConfig>, Stri
     }
 }
 
+/// 损坏配置备份键的前缀，后接 Unix 时间戳（同一秒内的多次备份再加 `_序号`）
+const CONFIG_BACKUP_PREFIX: &str = "proxy_configs_backup_";
+
+// 将无法解析的 proxy_configs 原样复制到备份键，返回备份键名
+fn backup_corrupt_configs<R: tauri::Runtime>(
+    store: &tauri_plugin_store::Store<R>,
+    value: serde_json::Value,
+) -> String {
+    let timestamp = chrono::Utc::now().timestamp();
+    let mut key = format!("{CONFIG_BACKUP_PREFIX}{timestamp}");
+    let mut sequence = 1;
+    while store.has(&key) {
+        key = format!("{CONFIG_BACKUP_PREFIX}{timestamp}_{sequence}");
+        sequence += 1;
+    }
+    warn!("Backing up corrupt proxy_configs to {key}");
+    store.set(key.clone(), value);
+    key
+}
+
+#[tauri::command]
+async fn restore_from_backup(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    backup_key: String,
+) -> Result<usize, String> {
+    if !backup_key.starts_with(CONFIG_BACKUP_PREFIX) {
+        return Err(format!("Not a config backup key: {backup_key}"));
+    }
+
+    let _guard = state.config_lock.lock().await;
+    let store = app
+        .store("store.json")
+        .map_err(|e| format!("Failed to open store: {e}"))?;
+    let backup = store
+        .get(&backup_key)
+        .ok_or_else(|| format!("Backup not found: {backup_key}"))?;
+
+    // 按 id 合并到当前配置列表：备份之后新建或修改过的同 id 配置保持不变，
+    // 逐条恢复可解析的配置，有无法解析的条目时保留备份供手动处理
+    let items = backup
+        .as_array()
+        .ok_or_else(|| "Backup is not a config list".to_string())?;
+    let restored: Vec<ProxyConfig> = items
+        .iter()
+        .filter_map(|item| serde_json::from_value::<ProxyConfig>(item.clone()).ok())
+        .collect();
+    let skipped = items.len() - restored.len();
+
+    let mut configs = load_configs(&app)?;
+    let mut next_order = configs.iter().map(|c| c.order + 1).max().unwrap_or(0);
+    let mut count = 0;
+    for mut config in restored {
+        if configs.iter().any(|c| c.id == config.id) {
+            continue;
+        }
+        config.is_running = false;
+        config.order = next_order;
+        next_order += 1;
+        configs.push(config);
+        count += 1;
+    }
+
+    let value =
+        serde_json::to_value(&configs).map_err(|e| format!("Failed to serialize configs: {e}"))?;
+    store.set("proxy_configs", value);
+    if skipped == 0 {
+        store.delete(&backup_key);
+    }
+    store
+        .save()
+        .map_err(|e| format!("Failed to save store: {e}"))?;
+
+    info!("Restored {count} configs from {backup_key}, {skipped} malformed entries skipped");
+    Ok(count)
+}
+
+/// 列出损坏配置的备份键（按时间从旧到新）
+#[tauri::command]
+async fn list_backups(app: tauri::AppHandle) -> Result<Vec<String>, String> {
+    let store = app
+        .store("store.json")
+        .map_err(|e| format!("Failed to open store: {e}"))?;
+    let mut keys: Vec<String> = store
+        .keys()
+        .into_iter()
+        .filter(|key| key.starts_with(CONFIG_BACKUP_PREFIX))
+        .collect();
+    keys.sort_by_key(|key| {
+        let suffix = &key[CONFIG_BACKUP_PREFIX.len()..];
+        let (timestamp, sequence) = suffix.split_once('_').unwrap_or((suffix, "0"));
+        (
+            timestamp.parse::<i64>().unwrap_or_default(),
+            sequence.parse::<u32>().unwrap_or_default(),
+        )
+    });
+    Ok(keys)
+}
+
+#[tauri::command]
+async fn diagnose_store(app: tauri::AppHandle) -> Result<store_health::StoreDiagnosis, String> {
+    let store = app
+        .store("store.json")
+        .map_err(|e| format!("Failed to open store: {e}"))?;
+    let diagnosis = store_health::diagnose(store.get("proxy_configs").as_ref());
+    if !diagnosis.healthy {
+        warn!(
+            "Store has {} malformed config entries",
+            diagnosis.total_entries - diagnosis.valid_entries
+        );
+    }
+    Ok(diagnosis)
+}
+
 #[tauri::command]
-async fn save_config(app: tauri::AppHandle, config: ProxyConfig) -> Result<(), String> {
+async fn save_config(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    mut config: ProxyConfig,
+) -> Result<(), ErrorResponse> {
     // 验证配置
     if config.name.trim().is_empty() {
-        return Err("Config name cannot be empty".to_string());
+        return Err("Config name cannot be empty".to_string().into());
     }
 
-    if config.listen_port == 0 {
-        return Err("Invalid port number".to_string());
+    validate_proxy_config(&config)?;
+
+    // 未填写 remote_host 时从上游地址推导
+    if config.remote_host.trim().is_empty() {
+        let url = parse_remote_address(&config.remote_address)?;
+        config.remote_host = match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => format!("{host}:{port}"),
+            (Some(host), None) => host.to_string(),
+            (None, _) => String::new(),
+        };
     }
 
     let store = app.store("store.json").map_err(|e| {
         error!("Failed to open store: {e}");
         format!("Failed to open store: {e}")
     })?;
+    save_config_inner(&store, &state.config_lock, config).await
+}
 
+// 在 config_lock 保护下将配置写入存储：已存在时更新（保留排序位置），否则追加到末尾
+async fn save_config_inner<R: tauri::Runtime>(
+    store: &tauri_plugin_store::Store<R>,
+    config_lock: &Mutex<()>,
+    mut config: ProxyConfig,
+) -> Result<(), ErrorResponse> {
+    let _guard = config_lock.lock().await;
     let mut configs = match store.get("proxy_configs") {
         Some(value) => {
             serde_json::from_value::<Vec<ProxyConfig>>(value.clone()).unwrap_or_else(|e| {
+                // 覆盖前备份无法解析的原始数据，可通过 restore_from_backup 恢复
                 error!("Failed to deserialize existing configs: {e}, starting fresh");
+                backup_corrupt_configs(store, value);
                 Vec::new()
             })
         }
@@ -99,9 +271,13 @@ async fn save_config(app: tauri::AppHandle, config: ProxyConfig) -> Result<(), S
     let config_name = config.name.clone();
     if let Some(existing) = configs.iter_mut().find(|c| c.id == config.id) {
         info!("Updating existing config: {config_name}");
+        // 排序位置只通过 reorder_configs 修改，表单提交的值不可信
+        config.order = existing.order;
         *existing = config;
     } else {
         info!("Adding new config: {config_name}");
+        // 新配置排在列表末尾
+        config.order = configs.iter().map(|c| c.order + 1).max().unwrap_or(0);
         configs.push(config);
     }
 
@@ -118,6 +294,90 @@ async fn save_config(app: tauri::AppHandle, config: ProxyConfig) -> Result<(), S
     Ok(())
 }
 
+#[tauri::command]
+async fn rename_config(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    config_id: String,
+    new_name: String,
+) -> Result<(), String> {
+    let new_name = new_name.trim().to_string();
+    if new_name.is_empty() {
+        return Err("Config name cannot be empty".to_string());
+    }
+
+    let _guard = state.config_lock.lock().await;
+    let store = app
+        .store("store.json")
+        .map_err(|e| format!("Failed to open store: {e}"))?;
+    let mut configs = match store.get("proxy_configs") {
+        Some(value) => serde_json::from_value::<Vec<ProxyConfig>>(value.clone())
+            .map_err(|e| format!("Failed to deserialize configs: {e}"))?,
+        None => return Err("No configs found".to_string()),
+    };
+
+    let config = configs
+        .iter_mut()
+        .find(|c| c.id == config_id)
+        .ok_or_else(|| format!("Config not found: {config_id}"))?;
+    info!("Renaming config {} -> {new_name}", config.name);
+    config.name = new_name.clone();
+
+    let value =
+        serde_json::to_value(&configs).map_err(|e| format!("Failed to serialize configs: {e}"))?;
+    store.set("proxy_configs", value);
+    store
+        .save()
+        .map_err(|e| format!("Failed to save store: {e}"))?;
+
+    // 同步更新运行中的实例，后续日志和指标使用新名称
+    if rename_proxy_instance(state.proxy_manager.clone(), &config_id, &new_name).await {
+        info!("Updated running proxy name: {new_name}");
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+async fn reorder_configs(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    ordered_ids: Vec<String>,
+) -> Result<(), String> {
+    let _guard = state.config_lock.lock().await;
+    let store = app
+        .store("store.json")
+        .map_err(|e| format!("Failed to open store: {e}"))?;
+    let mut configs = match store.get("proxy_configs") {
+        Some(value) => serde_json::from_value::<Vec<ProxyConfig>>(value.clone())
+            .map_err(|e| format!("Failed to deserialize configs: {e}"))?,
+        None => return Err("No configs found".to_string()),
+    };
+
+    // 未出现在列表中的配置保持原有相对顺序，排在列表之后；列表中未知的 ID 忽略
+    configs.sort_by_key(|c| c.order);
+    let mut next = ordered_ids.len() as i64;
+    for config in configs.iter_mut() {
+        config.order = match ordered_ids.iter().position(|id| *id == config.id) {
+            Some(index) => index as i64,
+            None => {
+                next += 1;
+                next - 1
+            }
+        };
+    }
+
+    let value =
+        serde_json::to_value(&configs).map_err(|e| format!("Failed to serialize configs: {e}"))?;
+    store.set("proxy_configs", value);
+    store
+        .save()
+        .map_err(|e| format!("Failed to save store: {e}"))?;
+
+    info!("Reordered {} configs", configs.len());
+    Ok(())
+}
+
 #[tauri::command]
 async fn delete_config(
     app: tauri::AppHandle,
@@ -136,7 +396,8 @@ async fn delete_config(
         }
     }
 
-    // 从存储中删除配置
+    // 从存储中删除配置（停止代理之后再加锁，stop_proxy 内部同样需要该锁）
+    let _guard = state.config_lock.lock().await;
     let store = app
         .store("store.json")
         .map_err(|e| format!("Failed to open store: {e}"))?;
@@ -162,6 +423,10 @@ async fn delete_config(
         .save()
         .map_err(|e| format!("Failed to save store: {e}"))?;
 
+    // 清理该配置缓存的自签名证书和累计指标
+    cert_store::remove(&config_id);
+    metrics::forget(&config_id);
+
     info!("Config deleted successfully: {config_id}");
     Ok(())
 }
@@ -171,7 +436,7 @@ async fn start_proxy(
     app: tauri::AppHandle,
     state: State<'_, AppState>,
     config_id: String,
-) -> Result<(), String> {
+) -> Result<u16, ErrorResponse> {
     info!("Starting proxy: {config_id}");
 
     // 检查代理是否已经在运行
@@ -179,7 +444,7 @@ async fn start_proxy(
         let proxy_manager = state.proxy_manager.read().await;
         if proxy_manager.contains_key(&config_id) {
             warn!("Proxy already running: {config_id}");
-            return Err(format!("Proxy already running: {config_id}"));
+            return Err(format!("Proxy already running: {config_id}").into());
         }
     }
 
@@ -190,7 +455,7 @@ async fn start_proxy(
     let configs = match store.get("proxy_configs") {
         Some(value) => serde_json::from_value::<Vec<ProxyConfig>>(value.clone())
             .map_err(|e| format!("Failed to deserialize configs: {e}"))?,
-        None => return Err("No configs found".to_string()),
+        None => return Err("No configs found".to_string().into()),
     };
 
     let config = configs
@@ -199,21 +464,35 @@ async fn start_proxy(
         .ok_or_else(|| format!("Config not found: {config_id}"))?
         .clone();
 
-    // 调用辅助函数来启动代理
-    start_proxy_helper(state.proxy_manager.clone(), config.clone()).await?;
+    // 调用辅助函数来启动代理，返回实际监听的端口
+    let bound_port = start_proxy_helper(state.proxy_manager.clone(), config.clone()).await?;
 
     // 更新配置状态为运行中
+    let _guard = state.config_lock.lock().await;
     let store = app
         .store("store.json")
         .map_err(|e| format!("Failed to open store: {e}"))?;
     let mut configs = match store.get("proxy_configs") {
         Some(value) => serde_json::from_value::<Vec<ProxyConfig>>(value.clone())
             .map_err(|e| format!("Failed to deserialize configs: {e}"))?,
-        None => return Err("No configs found".to_string()),
+        None => return Err("No configs found".to_string().into()),
     };
 
     if let Some(config) = configs.iter_mut().find(|c| c.id == config_id) {
         config.is_running = true;
+        // 端口为 0 时保留模板端口，仅记录系统分配的实际地址；
+        // 自动递增选择了其他端口时同时更新配置端口
+        if config.listen_port != bound_port {
+            let scheme = if config.use_https { "https" } else { "http" };
+            config.listen_address = format!("{scheme}://{}:{bound_port}", config.listen_ip);
+        }
+        if config.listen_port != 0 && config.listen_port != bound_port {
+            info!(
+                "Port changed for {}: {} -> {bound_port}",
+                config.name, config.listen_port
+            );
+            config.listen_port = bound_port;
+        }
         info!("Updated config status to running: {}", config.name);
     }
 
@@ -225,7 +504,7 @@ async fn start_proxy(
         .save()
         .map_err(|e| format!("Failed to save store: {e}"))?;
 
-    Ok(())
+    Ok(bound_port)
 }
 
 #[tauri::command]
@@ -252,6 +531,7 @@ async fn stop_proxy(
     })?;
 
     // 更新配置状态
+    let _guard = state.config_lock.lock().await;
     let store = app
         .store("store.json")
         .map_err(|e| format!("Failed to open store: {e}"))?;
@@ -266,6 +546,10 @@ async fn stop_proxy(
         info!("Updated config status to stopped: {}", config.name);
     }
 
+    if metrics_persistence_enabled(&app) {
+        store.set("metrics", serde_json::json!(metrics::persisted_totals()));
+    }
+
     let value =
         serde_json::to_value(&configs).map_err(|e| format!("Failed to serialize configs: {e}"))?;
 
@@ -278,75 +562,923 @@ async fn stop_proxy(
 }
 
 #[tauri::command]
-async fn check_port(ip: String, port: u16) -> Result<bool, String> {
-    if port == 0 {
-        return Ok(false);
+async fn restart_proxy(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    config_id: String,
+) -> Result<u16, ErrorResponse> {
+    info!("Restarting proxy: {config_id}");
+
+    // 先读取并校验最新配置，有误时保持当前实例继续运行
+    let mut config = load_configs(&app)?
+        .into_iter()
+        .find(|c| c.id == config_id)
+        .ok_or_else(|| format!("Config not found: {config_id}"))?;
+    validate_proxy_config(&config)?;
+
+    // 与 start_proxy 一致，自动递增选择了其他端口时写回存储（端口为 0 时保留模板端口）
+    let requested_port =
+        (config.auto_increment_port && config.listen_port != 0).then_some(config.listen_port);
+
+    let instance = {
+        let mut proxy_manager = state.proxy_manager.write().await;
+        let running = proxy_manager
+            .get(&config_id)
+            .ok_or_else(|| format!("Proxy not running: {config_id}"))?;
+
+        // 先启动新实例再停止旧实例，端口始终有监听；同一端口只有新旧监听都启用端口复用时
+        // 才能同时绑定，否则拒绝重启并保持当前实例运行，避免出现端口空闲、被其他进程占用的窗口
+        if !(running.config.reuse_port && config.reuse_port) {
+            return Err(ErrorResponse {
+                error: format!(
+                    "Restarting {} without releasing its port requires reuse_port; stop and start it instead",
+                    config.name
+                ),
+                code: "REUSE_PORT_REQUIRED".to_string(),
+            });
+        }
+
+        // 系统分配端口（0）时重新绑定当前实际端口，避免重启后地址变化
+        if config.listen_port == 0 {
+            config.listen_port = running.config.listen_port;
+        }
+
+        proxy_manager
+            .remove(&config_id)
+            .ok_or_else(|| format!("Proxy not running: {config_id}"))?
+    };
+
+    // 新实例启动时读取累计指标，需先记录旧实例的计数
+    let previous_metrics = instance.state.metrics.clone();
+    let since = previous_metrics.lifetime();
+    metrics::remember(&config_id, &previous_metrics);
+    match start_proxy_helper(state.proxy_manager.clone(), config).await {
+        Ok(bound_port) => {
+            if let Err(e) = stop_proxy_server(instance).await {
+                warn!("Error while stopping previous instance of proxy {config_id}: {e}");
+            }
+            // 旧实例在新实例启动后、停止前处理的请求并入新实例的累计指标
+            if let Some(restarted) = state.proxy_manager.read().await.get(&config_id) {
+                restarted
+                    .state
+                    .metrics
+                    .absorb_since(&previous_metrics, since);
+            }
+
+            let _guard = state.config_lock.lock().await;
+            set_running_flags(&app, std::slice::from_ref(&config_id), true)?;
+            if requested_port.is_some_and(|port| port != bound_port) {
+                record_bound_ports(&app, &[(config_id.clone(), bound_port)])?;
+            }
+
+            info!("Proxy restarted on port {bound_port}: {config_id}");
+            Ok(bound_port)
+        }
+        Err(e) => {
+            // 新实例启动失败时旧实例继续运行
+            error!("Failed to restart proxy {config_id}, keeping the current instance: {e}");
+            state
+                .proxy_manager
+                .write()
+                .await
+                .insert(config_id, instance);
+            Err(e.into())
+        }
     }
+}
 
-    // 验证 IP 地址
-    if ip != "127.0.0.1" && ip != "0.0.0.0" {
-        return Ok(false);
+#[tauri::command]
+async fn drain_proxy(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    config_id: String,
+    grace_seconds: u64,
+) -> Result<u64, String> {
+    let shared = {
+        let proxy_manager = state.proxy_manager.read().await;
+        proxy_manager
+            .get(&config_id)
+            .map(|instance| instance.state.clone())
+            .ok_or_else(|| format!("Proxy not found: {config_id}"))?
+    };
+
+    info!("Draining proxy {config_id} (grace {grace_seconds}s)");
+    let remaining =
+        proxy_manager::drain_proxy(&shared, std::time::Duration::from_secs(grace_seconds)).await;
+    if remaining > 0 {
+        warn!("Grace period elapsed with {remaining} requests still in flight: {config_id}");
     }
 
-    Ok(check_port_available(&ip, port))
+    stop_proxy(app, state, config_id).await?;
+    Ok(remaining)
 }
 
 #[tauri::command]
-async fn create_default_config() -> Result<ProxyConfig, String> {
-    Ok(ProxyConfig::default())
+async fn stop_proxy_by_port(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    listen_ip: String,
+    port: u16,
+) -> Result<String, ErrorResponse> {
+    // 运行中实例的配置记录的是实际监听端口；按网卡监听时实际地址记录在 listen_address
+    let config_id = {
+        let proxy_manager = state.proxy_manager.read().await;
+        proxy_manager
+            .values()
+            .find(|instance| {
+                let bound_ip = instance
+                    .config
+                    .listen_address
+                    .parse::<std::net::SocketAddr>()
+                    .ok()
+                    .map(|addr| addr.ip().to_string());
+                (instance.config.listen_ip == listen_ip.trim()
+                    || bound_ip.as_deref() == Some(listen_ip.trim()))
+                    && instance.config.listen_ports().any(|p| p == port)
+            })
+            .map(|instance| instance.config.id.clone())
+    };
+
+    let config_id = config_id.ok_or_else(|| ErrorResponse {
+        error: format!("No running proxy bound to {listen_ip}:{port}"),
+        code: "NOT_FOUND".to_string(),
+    })?;
+
+    info!("Stopping proxy bound to {listen_ip}:{port}: {config_id}");
+    stop_proxy(app, state, config_id.clone()).await?;
+    Ok(config_id)
 }
 
-// 优雅关闭所有代理
-async fn shutdown_all_proxies(proxy_manager: ProxyManager) {
-    info!("Shutting down all proxies...");
-    let mut manager = proxy_manager.write().await;
-    let proxy_count = manager.len();
+// 批量更新存储中配置的运行状态，调用方需持有 config_lock
+fn set_running_flags(app: &tauri::AppHandle, ids: &[String], running: bool) -> Result<(), String> {
+    let store = app
+        .store("store.json")
+        .map_err(|e| format!("Failed to open store: {e}"))?;
+    let mut configs = match store.get("proxy_configs") {
+        Some(value) => serde_json::from_value::<Vec<ProxyConfig>>(value.clone())
+            .map_err(|e| format!("Failed to deserialize configs: {e}"))?,
+        None => return Err("No configs found".to_string()),
+    };
 
-    if proxy_count > 0 {
-        info!("Stopping {proxy_count} running proxies");
-        for (id, instance) in manager.drain() {
-            info!("Stopping proxy {id} on app exit");
-            if let Err(e) = stop_proxy_server(instance).await {
-                error!("Failed to stop proxy {id}: {e}");
-            }
+    for config in configs.iter_mut().filter(|c| ids.contains(&c.id)) {
+        config.is_running = running;
+    }
+
+    let value =
+        serde_json::to_value(&configs).map_err(|e| format!("Failed to serialize configs: {e}"))?;
+
+    store.set("proxy_configs", value);
+    store
+        .save()
+        .map_err(|e| format!("Failed to save store: {e}"))?;
+
+    Ok(())
+}
+
+// 将自动递增后的端口写回存储，调用方需持有 config_lock
+fn record_bound_ports(app: &tauri::AppHandle, ports: &[(String, u16)]) -> Result<(), String> {
+    let store = app
+        .store("store.json")
+        .map_err(|e| format!("Failed to open store: {e}"))?;
+    let mut configs = match store.get("proxy_configs") {
+        Some(value) => serde_json::from_value::<Vec<ProxyConfig>>(value.clone())
+            .map_err(|e| format!("Failed to deserialize configs: {e}"))?,
+        None => return Err("No configs found".to_string()),
+    };
+
+    for (id, port) in ports {
+        if let Some(config) = configs.iter_mut().find(|c| c.id == *id) {
+            info!(
+                "Port changed for {}: {} -> {port}",
+                config.name, config.listen_port
+            );
+            let scheme = if config.use_https { "https" } else { "http" };
+            config.listen_port = *port;
+            config.listen_address = format!("{scheme}://{}:{port}", config.listen_ip);
         }
-        info!("All proxies stopped");
     }
+
+    let value =
+        serde_json::to_value(&configs).map_err(|e| format!("Failed to serialize configs: {e}"))?;
+
+    store.set("proxy_configs", value);
+    store
+        .save()
+        .map_err(|e| format!("Failed to save store: {e}"))?;
+
+    Ok(())
 }
 
-#[cfg_attr(mobile, tauri::mobile_entry_point)]
-pub fn run() {
-    // 初始化日志
-    env_logger::Builder::from_default_env()
-        .filter_level(log::LevelFilter::Info)
-        .format_timestamp_millis()
-        .init();
+#[tauri::command]
+async fn start_all(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<HashMap<String, BulkActionResult>, String> {
+    info!("Starting all proxies");
 
-    info!("Starting Reverse Proxy GUI application");
+    let configs = load_configs(&app)?;
+    start_configs(&app, &state, configs).await
+}
 
-    tauri::Builder::default()
-        .plugin(tauri_plugin_store::Builder::default().build())
-        .setup(|app| {
-            // 安装加密提供者
-            let _ = rustls::crypto::ring::default_provider().install_default();
+#[tauri::command]
+async fn stop_all(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<HashMap<String, BulkActionResult>, String> {
+    info!("Stopping all proxies");
 
-            // 初始化应用状态
-            let state = AppState {
-                proxy_manager: Arc::new(RwLock::new(HashMap::new())),
-            };
-            app.manage(state);
+    let instances: Vec<(String, ProxyInstance)> = {
+        let mut proxy_manager = state.proxy_manager.write().await;
+        proxy_manager.drain().collect()
+    };
 
-            // 获取 store
-            let store = app.store("store.json").map_err(|e| {
-                error!("Failed to open store during setup: {e}");
-                format!("Failed to open store: {e}")
-            })?;
+    stop_instances(&app, &state, instances).await
+}
 
-            // 启动时重置所有代理的运行状态
-            if let Some(value) = store.get("proxy_configs") {
-                match serde_json::from_value::<Vec<ProxyConfig>>(value.clone()) {
-                    Ok(mut configs) => {
-                        info!("Resetting running status for {} configs", configs.len());
+#[tauri::command]
+async fn start_by_tag(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    tag: String,
+) -> Result<HashMap<String, BulkActionResult>, String> {
+    info!("Starting proxies tagged {tag}");
+
+    let configs = load_configs(&app)?
+        .into_iter()
+        .filter(|c| c.tags.contains(&tag))
+        .collect();
+    start_configs(&app, &state, configs).await
+}
+
+#[tauri::command]
+async fn stop_by_tag(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    tag: String,
+) -> Result<HashMap<String, BulkActionResult>, String> {
+    info!("Stopping proxies tagged {tag}");
+
+    // 只处理运行中的实例，已停止的配置直接跳过
+    let instances: Vec<(String, ProxyInstance)> = {
+        let mut proxy_manager = state.proxy_manager.write().await;
+        let ids: Vec<String> = proxy_manager
+            .iter()
+            .filter(|(_, instance)| instance.config.tags.contains(&tag))
+            .map(|(id, _)| id.clone())
+            .collect();
+        ids.into_iter()
+            .filter_map(|id| proxy_manager.remove(&id).map(|instance| (id, instance)))
+            .collect()
+    };
+
+    stop_instances(&app, &state, instances).await
+}
+
+// 从存储中读取全部配置，未保存过时返回空列表
+fn load_configs(app: &tauri::AppHandle) -> Result<Vec<ProxyConfig>, String> {
+    let store = app
+        .store("store.json")
+        .map_err(|e| format!("Failed to open store: {e}"))?;
+    match store.get("proxy_configs") {
+        Some(value) => serde_json::from_value::<Vec<ProxyConfig>>(value.clone())
+            .map_err(|e| format!("Failed to deserialize configs: {e}")),
+        None => Ok(Vec::new()),
+    }
+}
+
+// 批量启动配置并更新存储中的运行状态（已运行或禁用的配置会被跳过）
+async fn start_configs(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    configs: Vec<ProxyConfig>,
+) -> Result<HashMap<String, BulkActionResult>, String> {
+    let auto_increment: HashMap<String, u16> = configs
+        .iter()
+        .filter(|c| c.auto_increment_port && c.listen_port != 0)
+        .map(|c| (c.id.clone(), c.listen_port))
+        .collect();
+
+    let results = start_all_enabled_proxies(state.proxy_manager.clone(), configs).await;
+
+    let started: Vec<String> = results
+        .iter()
+        .filter(|(_, result)| result.success)
+        .map(|(id, _)| id.clone())
+        .collect();
+
+    // 记录自动递增后实际使用的端口
+    let changed_ports: Vec<(String, u16)> = {
+        let proxy_manager = state.proxy_manager.read().await;
+        started
+            .iter()
+            .filter_map(|id| {
+                let port = proxy_manager.get(id)?.config.listen_port;
+                (auto_increment
+                    .get(id)
+                    .is_some_and(|&original| original != port))
+                .then(|| (id.clone(), port))
+            })
+            .collect()
+    };
+
+    let _guard = state.config_lock.lock().await;
+    set_running_flags(app, &started, true)?;
+    if !changed_ports.is_empty() {
+        record_bound_ports(app, &changed_ports)?;
+    }
+
+    info!("Started {} proxies", started.len());
+    Ok(results)
+}
+
+// 停止已从管理器移除的实例，并在存储中标记为未运行
+async fn stop_instances(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    instances: Vec<(String, ProxyInstance)>,
+) -> Result<HashMap<String, BulkActionResult>, String> {
+    let mut results = HashMap::new();
+    for (id, instance) in instances {
+        let result = match stop_proxy_server(instance).await {
+            Ok(()) => BulkActionResult::ok(),
+            Err(e) => {
+                error!("Failed to stop proxy {id}: {e}");
+                BulkActionResult::err(format!("Failed to stop proxy: {e}"))
+            }
+        };
+        results.insert(id, result);
+    }
+
+    // 实例已从管理器移除，无论停止是否出错都标记为未运行
+    let stopped: Vec<String> = results.keys().cloned().collect();
+    let _guard = state.config_lock.lock().await;
+    set_running_flags(app, &stopped, false)?;
+
+    Ok(results)
+}
+
+#[tauri::command]
+async fn reload_proxy(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    config_id: String,
+) -> Result<(), String> {
+    info!("Reloading proxy: {config_id}");
+
+    // 从存储中读取最新配置
+    let store = app
+        .store("store.json")
+        .map_err(|e| format!("Failed to open store: {e}"))?;
+    let configs = match store.get("proxy_configs") {
+        Some(value) => serde_json::from_value::<Vec<ProxyConfig>>(value.clone())
+            .map_err(|e| format!("Failed to deserialize configs: {e}"))?,
+        None => return Err("No configs found".to_string()),
+    };
+
+    let config = configs
+        .into_iter()
+        .find(|c| c.id == config_id)
+        .ok_or_else(|| format!("Config not found: {config_id}"))?;
+
+    // 在不重新绑定端口的情况下替换运行中的代理状态
+    reload_proxy_helper(state.proxy_manager.clone(), config).await
+}
+
+#[tauri::command]
+async fn tail_access_log(
+    app: tauri::AppHandle,
+    config_id: String,
+    lines: usize,
+) -> Result<Vec<String>, String> {
+    let store = app
+        .store("store.json")
+        .map_err(|e| format!("Failed to open store: {e}"))?;
+    let configs = match store.get("proxy_configs") {
+        Some(value) => serde_json::from_value::<Vec<ProxyConfig>>(value.clone())
+            .map_err(|e| format!("Failed to deserialize configs: {e}"))?,
+        None => return Ok(Vec::new()),
+    };
+
+    let config = configs
+        .into_iter()
+        .find(|c| c.id == config_id)
+        .ok_or_else(|| format!("Config not found: {config_id}"))?;
+
+    // 未配置日志路径或文件不存在时返回空列表，方便前端轮询
+    let Some(path) = config.access_log_path.filter(|p| !p.trim().is_empty()) else {
+        return Ok(Vec::new());
+    };
+    let path = std::path::Path::new(&path);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    access_log::tail_lines(path, lines).map_err(|e| format!("Failed to read access log: {e}"))
+}
+
+#[tauri::command]
+async fn regenerate_cert(app: tauri::AppHandle, config_id: String) -> Result<(), String> {
+    info!("Regenerating certificate: {config_id}");
+
+    let store = app
+        .store("store.json")
+        .map_err(|e| format!("Failed to open store: {e}"))?;
+    let configs = match store.get("proxy_configs") {
+        Some(value) => serde_json::from_value::<Vec<ProxyConfig>>(value.clone())
+            .map_err(|e| format!("Failed to deserialize configs: {e}"))?,
+        None => return Err("No configs found".to_string()),
+    };
+
+    let config = configs
+        .into_iter()
+        .find(|c| c.id == config_id)
+        .ok_or_else(|| format!("Config not found: {config_id}"))?;
+
+    // 新证书在下次启动代理时生效
+    cert_store::regenerate(&config).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[tauri::command]
+async fn reload_cert(state: State<'_, AppState>, config_id: String) -> Result<(), ErrorResponse> {
+    let proxy_manager = state.proxy_manager.read().await;
+    let instance = proxy_manager
+        .get(&config_id)
+        .ok_or_else(|| format!("Proxy not found: {config_id}"))?;
+    reload_tls_certificates(instance)?;
+    Ok(())
+}
+
+#[tauri::command]
+async fn export_proxy_cert(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    config_id: String,
+) -> Result<String, String> {
+    // 运行中的代理导出其实际提供的证书
+    if let Some(cert_pem) = state
+        .proxy_manager
+        .read()
+        .await
+        .get(&config_id)
+        .and_then(|instance| instance.cert_pem.clone())
+    {
+        return Ok(cert_pem);
+    }
+
+    let store = app
+        .store("store.json")
+        .map_err(|e| format!("Failed to open store: {e}"))?;
+    let configs = match store.get("proxy_configs") {
+        Some(value) => serde_json::from_value::<Vec<ProxyConfig>>(value.clone())
+            .map_err(|e| format!("Failed to deserialize configs: {e}"))?,
+        None => return Err("No configs found".to_string()),
+    };
+
+    let config = configs
+        .into_iter()
+        .find(|c| c.id == config_id)
+        .ok_or_else(|| format!("Config not found: {config_id}"))?;
+
+    if !config.use_https {
+        return Err(format!("Config {} does not use HTTPS", config.name));
+    }
+
+    cert_store::export_cert_pem(&config).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn get_proxy_metrics(
+    state: State<'_, AppState>,
+    config_id: String,
+) -> Result<metrics::MetricsSnapshot, String> {
+    let proxy_manager = state.proxy_manager.read().await;
+    let instance = proxy_manager
+        .get(&config_id)
+        .ok_or_else(|| format!("Proxy not found: {config_id}"))?;
+    Ok(instance.state.metrics.snapshot())
+}
+
+#[tauri::command]
+async fn reset_proxy_metrics(
+    state: State<'_, AppState>,
+    config_id: String,
+) -> Result<(), ErrorResponse> {
+    let proxy_manager = state.proxy_manager.read().await;
+    let instance = proxy_manager.get(&config_id).ok_or_else(|| ErrorResponse {
+        error: format!("Proxy not found: {config_id}"),
+        code: "NOT_FOUND".to_string(),
+    })?;
+    instance.state.metrics.reset();
+    info!("Reset metrics for proxy: {}", instance.config.name);
+    Ok(())
+}
+
+#[tauri::command]
+async fn export_har(state: State<'_, AppState>, config_id: String) -> Result<String, String> {
+    let proxy_manager = state.proxy_manager.read().await;
+    let instance = proxy_manager
+        .get(&config_id)
+        .ok_or_else(|| format!("Proxy not found: {config_id}"))?;
+    if !instance.state.current.load().config.capture_requests {
+        return Err(format!(
+            "Request capture is not enabled for config: {config_id}"
+        ));
+    }
+    serde_json::to_string_pretty(&instance.state.capture.to_har())
+        .map_err(|e| format!("Failed to serialize HAR: {e}"))
+}
+
+#[tauri::command]
+async fn get_proxy_info(
+    state: State<'_, AppState>,
+    config_id: String,
+) -> Result<ProxyInfo, String> {
+    let proxy_manager = state.proxy_manager.read().await;
+    proxy_manager
+        .get(&config_id)
+        .map(|instance| instance.info())
+        .ok_or_else(|| format!("Proxy not found: {config_id}"))
+}
+
+#[tauri::command]
+async fn get_proxy_status(
+    state: State<'_, AppState>,
+    config_id: String,
+) -> Result<ProxyStatus, String> {
+    let proxy_manager = state.proxy_manager.read().await;
+    Ok(proxy_manager
+        .get(&config_id)
+        .map(|instance| instance.status())
+        .unwrap_or_else(|| ProxyStatus::stopped(&config_id)))
+}
+
+#[tauri::command]
+async fn benchmark_proxy(
+    state: State<'_, AppState>,
+    config_id: String,
+    requests: u32,
+    concurrency: u32,
+) -> Result<benchmark::BenchResult, String> {
+    benchmark::validate(requests, concurrency)?;
+
+    // 仅对运行中的代理压测，目标为其实际监听地址（通配地址改用回环地址）
+    let (url, name) = {
+        let proxy_manager = state.proxy_manager.read().await;
+        let instance = proxy_manager
+            .get(&config_id)
+            .ok_or_else(|| format!("Proxy is not running: {config_id}"))?;
+        let config = &instance.config;
+        let listen_ip = config.bound_listen_ip();
+        let ip = match listen_ip.parse::<std::net::IpAddr>() {
+            Ok(std::net::IpAddr::V4(ip)) if ip.is_unspecified() => {
+                std::net::IpAddr::V4(std::net::Ipv4Addr::LOCALHOST)
+            }
+            Ok(std::net::IpAddr::V6(ip)) if ip.is_unspecified() => {
+                std::net::IpAddr::V6(std::net::Ipv6Addr::LOCALHOST)
+            }
+            Ok(ip) => ip,
+            Err(_) => return Err(format!("Invalid listen IP: {listen_ip}")),
+        };
+        let scheme = if config.use_https { "https" } else { "http" };
+        let addr = std::net::SocketAddr::new(ip, config.listen_port);
+        (format!("{scheme}://{addr}/"), config.name.clone())
+    };
+
+    info!("Benchmarking {name} at {url}: {requests} requests, concurrency {concurrency}");
+    let result = benchmark::run(&url, requests, concurrency).await?;
+    info!(
+        "Benchmark for {name} finished: {:.1} req/s, p99 {:.1}ms, error rate {:.2}%",
+        result.requests_per_second,
+        result.latency_p99_ms,
+        result.error_rate * 100.0
+    );
+    Ok(result)
+}
+
+#[tauri::command]
+async fn cleanup_orphaned_certs(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<usize, String> {
+    // 保留已保存配置和运行中代理的证书（持久化证书在配置删除前会被复用）
+    let mut keep_ids: HashSet<String> = state.proxy_manager.read().await.keys().cloned().collect();
+    let store = app
+        .store("store.json")
+        .map_err(|e| format!("Failed to open store: {e}"))?;
+    if let Some(value) = store.get("proxy_configs") {
+        let configs = serde_json::from_value::<Vec<ProxyConfig>>(value.clone())
+            .map_err(|e| format!("Failed to deserialize configs: {e}"))?;
+        keep_ids.extend(configs.into_iter().map(|c| c.id));
+    }
+
+    Ok(cert_store::cleanup_orphaned(&keep_ids))
+}
+
+#[tauri::command]
+async fn resolve_upstream(config: ProxyConfig) -> Result<Vec<String>, ErrorResponse> {
+    let addrs = resolve_upstream_addrs(&config).await?;
+    info!("Resolved upstream for {}: {addrs:?}", config.name);
+    Ok(addrs)
+}
+
+#[tauri::command]
+async fn test_upstream_proxy(
+    proxy_url: String,
+    target: Option<String>,
+) -> Result<ProxyTestResult, String> {
+    let result = proxy_manager::test_upstream_proxy(&proxy_url, target.as_deref()).await;
+    if let Some(error) = &result.error {
+        warn!("Upstream proxy test failed for {proxy_url}: {error}");
+    }
+    Ok(result)
+}
+
+#[tauri::command]
+async fn get_global_stats(state: State<'_, AppState>) -> Result<metrics::GlobalStats, String> {
+    // 仅持有管理器读锁，指标本身通过原子读取，不影响请求处理
+    let proxy_manager = state.proxy_manager.read().await;
+    let mut stats = metrics::GlobalStats::default();
+    for instance in proxy_manager.values() {
+        stats.add(&instance.state.metrics);
+    }
+    Ok(stats)
+}
+
+#[tauri::command]
+async fn check_port(ip: String, port: u16) -> Result<bool, String> {
+    // 端口 0 表示由系统分配空闲端口
+    if port == 0 {
+        return Ok(true);
+    }
+
+    // 验证 IP 地址
+    if ip != "127.0.0.1" && ip != "0.0.0.0" {
+        return Ok(false);
+    }
+
+    Ok(check_port_available(&ip, port))
+}
+
+#[tauri::command]
+async fn find_port_conflicts(app: tauri::AppHandle) -> Result<Vec<(String, String)>, String> {
+    let store = app
+        .store("store.json")
+        .map_err(|e| format!("Failed to open store: {e}"))?;
+    let configs = match store.get("proxy_configs") {
+        Some(value) => serde_json::from_value::<Vec<ProxyConfig>>(value.clone())
+            .map_err(|e| format!("Failed to deserialize configs: {e}"))?,
+        None => return Ok(Vec::new()),
+    };
+
+    let conflicts = find_port_conflicts_in(&configs);
+    if !conflicts.is_empty() {
+        warn!("Found {} port conflicts", conflicts.len());
+    }
+    Ok(conflicts)
+}
+
+#[tauri::command]
+async fn validate_config(config: ProxyConfig) -> Result<Vec<ValidationWarning>, String> {
+    Ok(collect_validation_issues(&config))
+}
+
+#[tauri::command]
+async fn get_shutdown_grace() -> Result<u64, String> {
+    Ok(shutdown_grace_seconds())
+}
+
+#[tauri::command]
+async fn set_shutdown_grace(app: tauri::AppHandle, seconds: u64) -> Result<(), String> {
+    let store = app
+        .store("store.json")
+        .map_err(|e| format!("Failed to open store: {e}"))?;
+
+    store.set("shutdown_grace_seconds", serde_json::json!(seconds));
+    store
+        .save()
+        .map_err(|e| format!("Failed to save store: {e}"))?;
+
+    set_shutdown_grace_seconds(seconds);
+    info!("Shutdown grace period set to {seconds}s");
+    Ok(())
+}
+
+/// 模板中忽略的字段，这些字段总是由新配置自行生成
+const TEMPLATE_IGNORED_FIELDS: [&str; 3] = ["id", "created_at", "is_running"];
+
+// 将模板（部分 ProxyConfig）覆盖到内置默认值上，始终生成新的 ID 和创建时间
+fn apply_default_template(template: &serde_json::Value) -> Result<ProxyConfig, String> {
+    let template = template
+        .as_object()
+        .ok_or_else(|| "Default template must be a JSON object".to_string())?;
+
+    let mut merged = serde_json::to_value(ProxyConfig::default())
+        .map_err(|e| format!("Failed to serialize default config: {e}"))?;
+    if let Some(fields) = merged.as_object_mut() {
+        for (key, value) in template {
+            if !TEMPLATE_IGNORED_FIELDS.contains(&key.as_str()) {
+                fields.insert(key.clone(), value.clone());
+            }
+        }
+    }
+
+    serde_json::from_value(merged).map_err(|e| format!("Invalid default template: {e}"))
+}
+
+#[tauri::command]
+async fn create_default_config(app: tauri::AppHandle) -> Result<ProxyConfig, String> {
+    let template = app
+        .store("store.json")
+        .ok()
+        .and_then(|store| store.get("default_config_template"))
+        .filter(|v| !v.is_null());
+
+    match template {
+        Some(template) => apply_default_template(&template).or_else(|e| {
+            warn!("Ignoring stored default template: {e}");
+            Ok(ProxyConfig::default())
+        }),
+        None => Ok(ProxyConfig::default()),
+    }
+}
+
+#[tauri::command]
+async fn set_default_template(
+    app: tauri::AppHandle,
+    template: Option<serde_json::Value>,
+) -> Result<(), String> {
+    // 保存前校验模板可以与默认值合并为有效配置；传入 null 时清除模板
+    if let Some(template) = &template {
+        apply_default_template(template)?;
+    }
+
+    let store = app
+        .store("store.json")
+        .map_err(|e| format!("Failed to open store: {e}"))?;
+    match template {
+        Some(template) => store.set("default_config_template", template),
+        None => {
+            store.delete("default_config_template");
+        }
+    }
+    store
+        .save()
+        .map_err(|e| format!("Failed to save store: {e}"))?;
+
+    info!("Default config template updated");
+    Ok(())
+}
+
+#[tauri::command]
+async fn config_to_link(config: ProxyConfig) -> Result<String, String> {
+    share_link::config_to_link(&config)
+}
+
+#[tauri::command]
+async fn link_to_config(link: String) -> Result<ProxyConfig, String> {
+    share_link::link_to_config(&link)
+}
+
+/// 默认的累计指标刷新间隔（秒）
+const DEFAULT_METRICS_FLUSH_INTERVAL_SECONDS: u64 = 60;
+
+// 累计指标刷新间隔，设置为 0 时关闭持久化
+fn metrics_flush_interval(app: &tauri::AppHandle) -> u64 {
+    app.store("store.json")
+        .ok()
+        .and_then(|store| store.get("metrics_flush_interval_seconds"))
+        .and_then(|v| v.as_u64())
+        .unwrap_or(DEFAULT_METRICS_FLUSH_INTERVAL_SECONDS)
+}
+
+fn metrics_persistence_enabled(app: &tauri::AppHandle) -> bool {
+    metrics_flush_interval(app) > 0
+}
+
+// 将运行中代理的累计指标写入存储
+async fn persist_metrics(
+    app: &tauri::AppHandle,
+    proxy_manager: &ProxyManager,
+) -> Result<(), String> {
+    {
+        let manager = proxy_manager.read().await;
+        for (id, instance) in manager.iter() {
+            metrics::remember(id, &instance.state.metrics);
+        }
+    }
+
+    let store = app
+        .store("store.json")
+        .map_err(|e| format!("Failed to open store: {e}"))?;
+    store.set("metrics", serde_json::json!(metrics::persisted_totals()));
+    store
+        .save()
+        .map_err(|e| format!("Failed to save store: {e}"))
+}
+
+// 优雅关闭所有代理
+async fn shutdown_all_proxies(proxy_manager: ProxyManager) {
+    info!("Shutting down all proxies...");
+    let mut manager = proxy_manager.write().await;
+    let proxy_count = manager.len();
+
+    if proxy_count > 0 {
+        info!("Stopping {proxy_count} running proxies");
+        for (id, instance) in manager.drain() {
+            info!("Stopping proxy {id} on app exit");
+            if let Err(e) = stop_proxy_server(instance).await {
+                error!("Failed to stop proxy {id}: {e}");
+            }
+        }
+        info!("All proxies stopped");
+    }
+}
+
+#[cfg_attr(mobile, tauri::mobile_entry_point)]
+pub fn run() {
+    // 初始化日志（输出到终端，同时以 app-log 事件转发给前端）
+    log_forwarder::init();
+
+    info!("Starting Reverse Proxy GUI application");
+
+    tauri::Builder::default()
+        .plugin(tauri_plugin_store::Builder::default().build())
+        .setup(|app| {
+            // 开始向前端转发日志
+            log_forwarder::attach(app.handle().clone());
+
+            // 安装加密提供者
+            let _ = rustls::crypto::ring::default_provider().install_default();
+
+            // 初始化自签名证书缓存目录
+            match app.path().app_data_dir() {
+                Ok(dir) => cert_store::init(dir.join("certs")),
+                Err(e) => warn!("Failed to resolve app data dir, using temp dir for certs: {e}"),
+            }
+
+            // 初始化应用状态
+            let proxy_manager: ProxyManager = Arc::new(RwLock::new(HashMap::new()));
+            let state = AppState {
+                proxy_manager: proxy_manager.clone(),
+                config_lock: Arc::new(Mutex::new(())),
+            };
+            app.manage(state);
+
+            // 获取 store
+            let store = app.store("store.json").map_err(|e| {
+                error!("Failed to open store during setup: {e}");
+                format!("Failed to open store: {e}")
+            })?;
+
+            // 读取停止代理时的等待时间设置
+            if let Some(seconds) = store.get("shutdown_grace_seconds").and_then(|v| v.as_u64()) {
+                info!("Using shutdown grace period of {seconds}s");
+                set_shutdown_grace_seconds(seconds);
+            }
+
+            // 载入累计指标并定期刷新到存储
+            let flush_interval = metrics_flush_interval(app.handle());
+            if flush_interval > 0 {
+                if let Some(value) = store.get("metrics") {
+                    match serde_json::from_value(value) {
+                        Ok(totals) => metrics::load_persisted(totals),
+                        Err(e) => warn!("Failed to deserialize persisted metrics: {e}"),
+                    }
+                }
+
+                let app_handle = app.handle().clone();
+                let proxy_manager = proxy_manager.clone();
+                tauri::async_runtime::spawn(async move {
+                    let mut interval =
+                        tokio::time::interval(std::time::Duration::from_secs(flush_interval));
+                    interval.tick().await;
+                    loop {
+                        interval.tick().await;
+                        if let Err(e) = persist_metrics(&app_handle, &proxy_manager).await {
+                            warn!("Failed to persist metrics: {e}");
+                        }
+                    }
+                });
+            }
+
+            // 启动时重置所有代理的运行状态
+            if let Some(value) = store.get("proxy_configs") {
+                match serde_json::from_value::<Vec<ProxyConfig>>(value.clone()) {
+                    Ok(mut configs) => {
+                        // 清理已删除配置或崩溃遗留的证书文件
+                        let keep_ids: HashSet<String> =
+                            configs.iter().map(|c| c.id.clone()).collect();
+                        cert_store::cleanup_orphaned(&keep_ids);
+
+                        // 记录上次退出时正在运行且开启了自动重启的配置
+                        let to_restart: Vec<ProxyConfig> = configs
+                            .iter()
+                            .filter(|c| c.is_running && c.auto_restart && c.enabled)
+                            .cloned()
+                            .collect();
+
+                        info!("Resetting running status for {} configs", configs.len());
                         for config in &mut configs {
                             if config.is_running {
                                 info!("Resetting running status for: {}", config.name);
@@ -359,10 +1491,38 @@ pub fn run() {
                         store
                             .save()
                             .map_err(|e| format!("Failed to save store: {e}"))?;
+
+                        // 自动重启上次运行中的代理，失败时记录日志并保持停止状态
+                        if !to_restart.is_empty() {
+                            let app_handle = app.handle().clone();
+                            tauri::async_runtime::spawn(async move {
+                                let mut restarted = Vec::new();
+                                for config in to_restart {
+                                    info!("Auto-restarting proxy: {}", config.name);
+                                    match start_proxy_helper(proxy_manager.clone(), config.clone())
+                                        .await
+                                    {
+                                        Ok(_) => restarted.push(config.id),
+                                        Err(e) => {
+                                            warn!("Failed to auto-restart {}: {e}", config.name)
+                                        }
+                                    }
+                                }
+                                let config_lock =
+                                    app_handle.state::<AppState>().config_lock.clone();
+                                let _guard = config_lock.lock().await;
+                                if let Err(e) = set_running_flags(&app_handle, &restarted, true) {
+                                    error!(
+                                        "Failed to update running status after auto-restart: {e}"
+                                    );
+                                }
+                            });
+                        }
                     }
                     Err(e) => {
                         error!("Failed to deserialize configs during setup: {e}");
-                        // 如果反序列化失败，则创建一个空的配置列表
+                        // 如果反序列化失败，先备份原始数据，再创建一个空的配置列表
+                        backup_corrupt_configs(&store, value);
                         store.set(
                             "proxy_configs",
                             serde_json::to_value(Vec::<ProxyConfig>::new()).unwrap(),
@@ -394,21 +1554,156 @@ pub fn run() {
                 let app_handle = window.app_handle();
                 if let Some(state) = app_handle.try_state::<AppState>() {
                     let proxy_manager = state.proxy_manager.clone();
-                    tauri::async_runtime::block_on(shutdown_all_proxies(proxy_manager));
+                    tauri::async_runtime::block_on(async {
+                        // 停止时会记录各代理的累计指标，随后统一写入存储
+                        shutdown_all_proxies(proxy_manager.clone()).await;
+                        if metrics_persistence_enabled(app_handle) {
+                            if let Err(e) = persist_metrics(app_handle, &proxy_manager).await {
+                                warn!("Failed to persist metrics on exit: {e}");
+                            }
+                        }
+                    });
                 }
             }
         })
         .invoke_handler(tauri::generate_handler![
             get_all_configs,
+            diagnose_store,
+            restore_from_backup,
+            list_backups,
             save_config,
+            rename_config,
+            reorder_configs,
             delete_config,
             start_proxy,
             stop_proxy,
+            restart_proxy,
+            drain_proxy,
+            stop_proxy_by_port,
+            start_all,
+            stop_all,
+            start_by_tag,
+            stop_by_tag,
+            reload_proxy,
+            tail_access_log,
+            regenerate_cert,
+            reload_cert,
+            export_proxy_cert,
+            get_proxy_metrics,
+            reset_proxy_metrics,
+            get_global_stats,
+            resolve_upstream,
+            test_upstream_proxy,
+            benchmark_proxy,
+            cleanup_orphaned_certs,
+            get_proxy_info,
+            get_proxy_status,
+            export_har,
             check_port,
-            create_default_config
+            find_port_conflicts,
+            validate_config,
+            get_shutdown_grace,
+            set_shutdown_grace,
+            create_default_config,
+            set_default_template,
+            config_to_link,
+            link_to_config
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 
     info!("Application shutting down");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tauri::test::MockRuntime;
+
+    /// 创建写入临时文件的存储（不落到应用数据目录下），返回应用、文件路径和存储
+    fn temp_store() -> (
+        tauri::App<MockRuntime>,
+        std::path::PathBuf,
+        Arc<tauri_plugin_store::Store<MockRuntime>>,
+    ) {
+        let app = tauri::test::mock_builder()
+            .plugin(tauri_plugin_store::Builder::new().build())
+            .build(tauri::test::mock_context(tauri::test::noop_assets()))
+            .unwrap();
+        let path = std::env::temp_dir().join(format!("store-test-{}.json", uuid::Uuid::new_v4()));
+        let store = tauri_plugin_store::StoreBuilder::new(&app, &path)
+            .disable_auto_save()
+            .build()
+            .unwrap();
+        (app, path, store)
+    }
+
+    fn test_config(id: &str) -> ProxyConfig {
+        ProxyConfig {
+            id: id.to_string(),
+            name: id.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn concurrent_saves_all_persist() {
+        let (_app, path, store) = temp_store();
+        let config_lock = Arc::new(Mutex::new(()));
+
+        let ids: Vec<String> = (0..16).map(|i| format!("concurrent_save_{i}")).collect();
+        let saves: Vec<_> = ids
+            .iter()
+            .map(|id| {
+                let store = store.clone();
+                let config_lock = config_lock.clone();
+                let config = test_config(id);
+                tokio::spawn(async move { save_config_inner(&store, &config_lock, config).await })
+            })
+            .collect();
+        for save in saves {
+            assert!(save.await.unwrap().is_ok());
+        }
+
+        let saved: Vec<ProxyConfig> =
+            serde_json::from_value(store.get("proxy_configs").unwrap()).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(saved.len(), ids.len());
+        for id in &ids {
+            assert!(saved.iter().any(|c| &c.id == id), "config {id} was lost");
+        }
+    }
+
+    #[tokio::test]
+    async fn save_backs_up_corrupt_configs() {
+        let (_app, path, store) = temp_store();
+        let corrupt = serde_json::json!([{ "id": "broken" }]);
+        store.set("proxy_configs", corrupt.clone());
+
+        assert!(
+            save_config_inner(&store, &Mutex::new(()), test_config("new"))
+                .await
+                .is_ok()
+        );
+
+        let backups: Vec<String> = store
+            .keys()
+            .into_iter()
+            .filter(|key| key.starts_with(CONFIG_BACKUP_PREFIX))
+            .collect();
+        let backup = backups.first().and_then(|key| store.get(key));
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(backups.len(), 1);
+        assert_eq!(backup, Some(corrupt));
+    }
+
+    #[test]
+    fn backups_in_the_same_second_get_unique_keys() {
+        let (_app, _path, store) = temp_store();
+        let first = backup_corrupt_configs(&store, serde_json::json!([1]));
+        let second = backup_corrupt_configs(&store, serde_json::json!([2]));
+        assert_ne!(first, second);
+        assert_eq!(store.get(&first), Some(serde_json::json!([1])));
+        assert_eq!(store.get(&second), Some(serde_json::json!([2])));
+    }
+}
@@ -0,0 +1,116 @@
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use serde::Serialize;
+use std::cell::Cell;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+use tauri::{AppHandle, Emitter};
+
+/// 转发给前端的事件名
+pub const LOG_EVENT: &str = "app-log";
+
+/// 转发给前端的最低日志级别
+const FORWARD_LEVEL: Level = Level::Info;
+
+/// 每秒最多转发的日志条数，超出的丢弃（终端输出不受影响）
+const MAX_EVENTS_PER_SECOND: u64 = 50;
+
+/// 日志事件负载
+#[derive(Debug, Clone, Serialize)]
+pub struct LogEvent {
+    pub level: String,
+    pub target: String,
+    pub message: String,
+    pub timestamp: String,
+}
+
+/// 在 env_logger 输出到 stderr 的同时，将日志以 Tauri 事件转发给前端
+struct ForwardingLogger {
+    inner: env_logger::Logger,
+    app: OnceLock<AppHandle>,
+    /// 当前计数窗口（Unix 秒）
+    window: AtomicU64,
+    /// 当前窗口内已转发的条数
+    count: AtomicU64,
+}
+
+thread_local! {
+    /// 发送事件期间产生的日志不再转发，避免递归
+    static FORWARDING: Cell<bool> = const { Cell::new(false) };
+}
+
+static LOGGER: OnceLock<&'static ForwardingLogger> = OnceLock::new();
+
+impl ForwardingLogger {
+    /// 固定窗口限流：每秒最多 MAX_EVENTS_PER_SECOND 条
+    fn allow(&self) -> bool {
+        let now = chrono::Utc::now().timestamp() as u64;
+        if self.window.swap(now, Ordering::Relaxed) != now {
+            self.count.store(0, Ordering::Relaxed);
+        }
+        self.count.fetch_add(1, Ordering::Relaxed) < MAX_EVENTS_PER_SECOND
+    }
+
+    fn forward(&self, record: &Record) {
+        let Some(app) = self.app.get() else {
+            return;
+        };
+        if record.level() > FORWARD_LEVEL || FORWARDING.with(Cell::get) || !self.allow() {
+            return;
+        }
+
+        let event = LogEvent {
+            level: record.level().to_string(),
+            target: record.target().to_string(),
+            message: record.args().to_string(),
+            timestamp: chrono::Local::now().to_rfc3339(),
+        };
+        FORWARDING.with(|f| f.set(true));
+        let _ = app.emit(LOG_EVENT, event);
+        FORWARDING.with(|f| f.set(false));
+    }
+}
+
+impl Log for ForwardingLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.inner.matches(record) {
+            return;
+        }
+        self.inner.log(record);
+        self.forward(record);
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+/// 初始化全局日志（替代直接调用 env_logger 的 init），RUST_LOG 仍然生效
+pub fn init() {
+    let inner = env_logger::Builder::from_default_env()
+        .filter_level(LevelFilter::Info)
+        .format_timestamp_millis()
+        .build();
+    let max_level = inner.filter();
+
+    let logger: &'static ForwardingLogger = Box::leak(Box::new(ForwardingLogger {
+        inner,
+        app: OnceLock::new(),
+        window: AtomicU64::new(0),
+        count: AtomicU64::new(0),
+    }));
+    if log::set_logger(logger).is_ok() {
+        log::set_max_level(max_level);
+        let _ = LOGGER.set(logger);
+    }
+}
+
+/// 应用启动后开始向前端转发日志
+pub fn attach(app: AppHandle) {
+    if let Some(logger) = LOGGER.get() {
+        let _ = logger.app.set(app);
+    }
+}
@@ -0,0 +1,372 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// 已持久化的累计指标（按配置 ID），启动代理时作为 lifetime 计数的起点
+static PERSISTED: OnceLock<Mutex<HashMap<String, LifetimeTotals>>> = OnceLock::new();
+
+fn persisted() -> &'static Mutex<HashMap<String, LifetimeTotals>> {
+    PERSISTED.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// 载入存储中的累计指标
+pub fn load_persisted(totals: HashMap<String, LifetimeTotals>) {
+    *persisted().lock().unwrap() = totals;
+}
+
+/// 当前所有配置的累计指标（用于写入存储）
+pub fn persisted_totals() -> HashMap<String, LifetimeTotals> {
+    persisted().lock().unwrap().clone()
+}
+
+/// 记录代理当前的累计指标，代理停止或定期刷新时调用
+pub fn remember(config_id: &str, metrics: &ProxyMetrics) {
+    persisted()
+        .lock()
+        .unwrap()
+        .insert(config_id.to_string(), metrics.lifetime());
+}
+
+/// 删除配置时清除其累计指标
+pub fn forget(config_id: &str) {
+    persisted().lock().unwrap().remove(config_id);
+}
+
+/// 跨重启累计的计数
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct LifetimeTotals {
+    pub requests_total: u64,
+    pub errors_total: u64,
+    pub bytes_received: u64,
+    pub bytes_sent: u64,
+}
+
+/// 单个代理的运行指标，全部使用原子计数，不阻塞请求处理
+#[derive(Debug, Default)]
+pub struct ProxyMetrics {
+    pub requests_total: AtomicU64,
+    pub errors_total: AtomicU64,
+    pub rejected_total: AtomicU64,
+    pub in_flight: AtomicU64,
+    /// 从客户端接收并转发到上游的请求体字节数
+    pub bytes_received: AtomicU64,
+    /// 从上游接收并返回给客户端的响应体字节数
+    pub bytes_sent: AtomicU64,
+    /// 本次启动前（或上次重置前）已累计的计数，重置时在锁内转移本次计数
+    lifetime_base: Mutex<LifetimeTotals>,
+    /// 按（方法，状态码）统计的请求数
+    by_label: Mutex<HashMap<(String, u16), u64>>,
+    /// 请求耗时直方图（各桶为非累计计数，导出时再累加）
+    duration_buckets: [AtomicU64; DURATION_BUCKETS.len()],
+    duration_sum_micros: AtomicU64,
+    duration_count: AtomicU64,
+}
+
+/// 请求耗时直方图的桶上界（秒）
+const DURATION_BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// 指标快照（返回给前端）
+#[derive(Debug, Clone, Serialize)]
+pub struct MetricsSnapshot {
+    pub requests_total: u64,
+    pub errors_total: u64,
+    pub rejected_total: u64,
+    pub in_flight: u64,
+    pub bytes_received: u64,
+    pub bytes_sent: u64,
+    /// 跨重启累计的计数（上面的字段仅统计本次启动）
+    pub lifetime: LifetimeTotals,
+}
+
+/// 所有运行中代理的汇总统计
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GlobalStats {
+    pub running_proxies: usize,
+    pub requests_total: u64,
+    pub errors_total: u64,
+    pub rejected_total: u64,
+    pub active_connections: u64,
+    pub bytes_received: u64,
+    pub bytes_sent: u64,
+}
+
+impl GlobalStats {
+    /// 累加一个代理的指标
+    pub fn add(&mut self, metrics: &ProxyMetrics) {
+        let snapshot = metrics.snapshot();
+        self.running_proxies += 1;
+        self.requests_total += snapshot.requests_total;
+        self.errors_total += snapshot.errors_total;
+        self.rejected_total += snapshot.rejected_total;
+        self.active_connections += snapshot.in_flight;
+        self.bytes_received += snapshot.bytes_received;
+        self.bytes_sent += snapshot.bytes_sent;
+    }
+}
+
+impl ProxyMetrics {
+    /// 以已持久化的累计指标为起点创建
+    pub fn restored(config_id: &str) -> Self {
+        Self {
+            lifetime_base: Mutex::new(
+                persisted()
+                    .lock()
+                    .unwrap()
+                    .get(config_id)
+                    .copied()
+                    .unwrap_or_default(),
+            ),
+            ..Default::default()
+        }
+    }
+
+    /// 累计计数 = 启动前累计 + 本次启动
+    pub fn lifetime(&self) -> LifetimeTotals {
+        let base = *self.lifetime_base.lock().unwrap();
+        LifetimeTotals {
+            requests_total: base.requests_total + self.requests_total.load(Ordering::Relaxed),
+            errors_total: base.errors_total + self.errors_total.load(Ordering::Relaxed),
+            bytes_received: base.bytes_received + self.bytes_received.load(Ordering::Relaxed),
+            bytes_sent: base.bytes_sent + self.bytes_sent.load(Ordering::Relaxed),
+        }
+    }
+
+    /// 重启时新旧实例短暂并行：将旧实例在 since 之后处理的计数并入本实例的累计基数
+    pub fn absorb_since(&self, previous: &ProxyMetrics, since: LifetimeTotals) {
+        let now = previous.lifetime();
+        let mut base = self.lifetime_base.lock().unwrap();
+        base.requests_total += now.requests_total.saturating_sub(since.requests_total);
+        base.errors_total += now.errors_total.saturating_sub(since.errors_total);
+        base.bytes_received += now.bytes_received.saturating_sub(since.bytes_received);
+        base.bytes_sent += now.bytes_sent.saturating_sub(since.bytes_sent);
+    }
+
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        MetricsSnapshot {
+            requests_total: self.requests_total.load(Ordering::Relaxed),
+            errors_total: self.errors_total.load(Ordering::Relaxed),
+            rejected_total: self.rejected_total.load(Ordering::Relaxed),
+            in_flight: self.in_flight.load(Ordering::Relaxed),
+            bytes_received: self.bytes_received.load(Ordering::Relaxed),
+            bytes_sent: self.bytes_sent.load(Ordering::Relaxed),
+            lifetime: self.lifetime(),
+        }
+    }
+
+    /// 开始处理一个请求，返回的守卫在释放时减少进行中的请求数
+    pub fn begin_request(self: &Arc<Self>) -> InFlightGuard {
+        self.requests_total.fetch_add(1, Ordering::Relaxed);
+        self.in_flight.fetch_add(1, Ordering::Relaxed);
+        InFlightGuard {
+            metrics: self.clone(),
+        }
+    }
+
+    /// 记录响应状态
+    pub fn record_status(&self, status: u16) {
+        if status >= 500 {
+            self.errors_total.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// 记录一次完成的请求（方法、状态码、耗时）
+    /// 非标准方法统一计为 OTHER，避免客户端构造任意方法名导致标签无限增长
+    pub fn observe(&self, method: &str, status: u16, elapsed: std::time::Duration) {
+        *self
+            .by_label
+            .lock()
+            .unwrap()
+            .entry((method_label(method).to_string(), status))
+            .or_default() += 1;
+
+        let seconds = elapsed.as_secs_f64();
+        if let Some(i) = DURATION_BUCKETS.iter().position(|b| seconds <= *b) {
+            self.duration_buckets[i].fetch_add(1, Ordering::Relaxed);
+        }
+        self.duration_sum_micros
+            .fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+        self.duration_count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// 按 Prometheus 文本格式导出（本次启动的计数）
+    pub fn render_prometheus(&self, proxy: &str) -> String {
+        use std::fmt::Write;
+
+        let proxy = escape_label(proxy);
+        let snapshot = self.snapshot();
+        let mut out = String::new();
+
+        let _ = writeln!(
+            out,
+            "# HELP reverse_proxy_requests_total Completed requests by method and status."
+        );
+        let _ = writeln!(out, "# TYPE reverse_proxy_requests_total counter");
+        let mut labels: Vec<_> = self
+            .by_label
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(k, v)| (k.clone(), *v))
+            .collect();
+        labels.sort();
+        for ((method, status), count) in labels {
+            let _ = writeln!(
+                out,
+                "reverse_proxy_requests_total{{proxy=\"{proxy}\",method=\"{}\",status=\"{status}\"}} {count}",
+                escape_label(&method)
+            );
+        }
+
+        let _ = writeln!(
+            out,
+            "# HELP reverse_proxy_request_duration_seconds Request duration."
+        );
+        let _ = writeln!(
+            out,
+            "# TYPE reverse_proxy_request_duration_seconds histogram"
+        );
+        let mut cumulative = 0;
+        for (bound, bucket) in DURATION_BUCKETS.iter().zip(&self.duration_buckets) {
+            cumulative += bucket.load(Ordering::Relaxed);
+            let _ = writeln!(
+                out,
+                "reverse_proxy_request_duration_seconds_bucket{{proxy=\"{proxy}\",le=\"{bound}\"}} {cumulative}"
+            );
+        }
+        let count = self.duration_count.load(Ordering::Relaxed);
+        let _ = writeln!(
+            out,
+            "reverse_proxy_request_duration_seconds_bucket{{proxy=\"{proxy}\",le=\"+Inf\"}} {count}"
+        );
+        let _ = writeln!(
+            out,
+            "reverse_proxy_request_duration_seconds_sum{{proxy=\"{proxy}\"}} {}",
+            self.duration_sum_micros.load(Ordering::Relaxed) as f64 / 1_000_000.0
+        );
+        let _ = writeln!(
+            out,
+            "reverse_proxy_request_duration_seconds_count{{proxy=\"{proxy}\"}} {count}"
+        );
+
+        let simple = [
+            (
+                "reverse_proxy_errors_total",
+                "counter",
+                "Responses with status >= 500.",
+                snapshot.errors_total,
+            ),
+            (
+                "reverse_proxy_rejected_total",
+                "counter",
+                "Requests rejected by the connection limit.",
+                snapshot.rejected_total,
+            ),
+            (
+                "reverse_proxy_received_bytes_total",
+                "counter",
+                "Request body bytes forwarded upstream.",
+                snapshot.bytes_received,
+            ),
+            (
+                "reverse_proxy_sent_bytes_total",
+                "counter",
+                "Response body bytes returned to clients.",
+                snapshot.bytes_sent,
+            ),
+            (
+                "reverse_proxy_in_flight",
+                "gauge",
+                "Requests currently being processed.",
+                snapshot.in_flight,
+            ),
+        ];
+        for (name, kind, help, value) in simple {
+            let _ = writeln!(out, "# HELP {name} {help}");
+            let _ = writeln!(out, "# TYPE {name} {kind}");
+            let _ = writeln!(out, "{name}{{proxy=\"{proxy}\"}} {value}");
+        }
+
+        out
+    }
+
+    /// 清零本次计数（进行中的请求数除外），清零前的计数并入累计值，lifetime 保持不变
+    pub fn reset(&self) {
+        let mut base = self.lifetime_base.lock().unwrap();
+        base.requests_total += self.requests_total.swap(0, Ordering::Relaxed);
+        base.errors_total += self.errors_total.swap(0, Ordering::Relaxed);
+        base.bytes_received += self.bytes_received.swap(0, Ordering::Relaxed);
+        base.bytes_sent += self.bytes_sent.swap(0, Ordering::Relaxed);
+        self.rejected_total.store(0, Ordering::Relaxed);
+
+        self.by_label.lock().unwrap().clear();
+        for bucket in &self.duration_buckets {
+            bucket.store(0, Ordering::Relaxed);
+        }
+        self.duration_sum_micros.store(0, Ordering::Relaxed);
+        self.duration_count.store(0, Ordering::Relaxed);
+    }
+
+    pub fn record_rejected(&self) {
+        self.rejected_total.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_bytes_received(&self, bytes: usize) {
+        self.bytes_received
+            .fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    pub fn record_bytes_sent(&self, bytes: usize) {
+        self.bytes_sent.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+}
+
+/// 转义 Prometheus 标签值
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// 指标使用的方法标签：标准方法原样保留，其余归为 OTHER
+fn method_label(method: &str) -> &str {
+    const KNOWN_METHODS: [&str; 9] = [
+        "GET", "HEAD", "POST", "PUT", "DELETE", "CONNECT", "OPTIONS", "TRACE", "PATCH",
+    ];
+    KNOWN_METHODS
+        .into_iter()
+        .find(|known| *known == method)
+        .unwrap_or("OTHER")
+}
+
+/// 进行中请求计数守卫
+pub struct InFlightGuard {
+    metrics: Arc<ProxyMetrics>,
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        self.metrics.in_flight.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn absorb_since_adds_only_requests_after_the_snapshot() {
+        let previous = Arc::new(ProxyMetrics::default());
+        drop(previous.begin_request());
+        let since = previous.lifetime();
+        drop(previous.begin_request());
+        drop(previous.begin_request());
+
+        let restarted = ProxyMetrics::default();
+        restarted.absorb_since(&previous, since);
+        assert_eq!(restarted.lifetime().requests_total, 2);
+    }
+}
@@ -0,0 +1,110 @@
+use crate::proxy_manager::{validate_proxy_config, Header, ProxyConfig};
+use base64::prelude::{Engine as _, BASE64_URL_SAFE_NO_PAD};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// 分享链接的 scheme 和路径
+const LINK_PREFIX: &str = "reverseproxygui://import";
+
+/// 分享链接的最大长度，超出时拒绝解析
+const MAX_LINK_LENGTH: usize = 8 * 1024;
+
+/// 当前的分享数据格式版本
+const SHARE_VERSION: u32 = 1;
+
+/// 分享链接中包含的配置字段（仅核心字段，不含运行状态和敏感凭据）
+#[derive(Debug, Serialize, Deserialize)]
+struct SharedConfig {
+    #[serde(rename = "v")]
+    version: u32,
+    name: String,
+    listen_ip: String,
+    listen_port: u16,
+    remote_address: String,
+    #[serde(default)]
+    remote_host: String,
+    #[serde(default)]
+    use_https: bool,
+    #[serde(default)]
+    headers: Vec<Header>,
+}
+
+/// 将配置编码为 reverseproxygui://import?data=... 分享链接
+pub fn config_to_link(config: &ProxyConfig) -> Result<String, String> {
+    let shared = SharedConfig {
+        version: SHARE_VERSION,
+        name: config.name.clone(),
+        listen_ip: config.listen_ip.clone(),
+        listen_port: config.listen_port,
+        remote_address: config.remote_address.clone(),
+        remote_host: config.remote_host.clone(),
+        use_https: config.use_https,
+        headers: config.headers.clone(),
+    };
+    let json =
+        serde_json::to_vec(&shared).map_err(|e| format!("Failed to serialize config: {e}"))?;
+
+    let link = format!("{LINK_PREFIX}?data={}", BASE64_URL_SAFE_NO_PAD.encode(json));
+    if link.len() > MAX_LINK_LENGTH {
+        return Err(format!(
+            "Config is too large to share ({} bytes, max {MAX_LINK_LENGTH})",
+            link.len()
+        ));
+    }
+    Ok(link)
+}
+
+/// 解析分享链接，生成带有新 ID 的配置（未保存）
+pub fn link_to_config(link: &str) -> Result<ProxyConfig, String> {
+    let link = link.trim();
+    if link.len() > MAX_LINK_LENGTH {
+        return Err(format!(
+            "Share link is too large ({} bytes, max {MAX_LINK_LENGTH})",
+            link.len()
+        ));
+    }
+
+    let url = url::Url::parse(link).map_err(|e| format!("Invalid share link: {e}"))?;
+    if !link.starts_with(LINK_PREFIX) || url.scheme() != "reverseproxygui" {
+        return Err(format!("Share link must start with {LINK_PREFIX}"));
+    }
+
+    let data = url
+        .query_pairs()
+        .find(|(key, _)| key == "data")
+        .map(|(_, value)| value.into_owned())
+        .ok_or_else(|| "Share link is missing the data parameter".to_string())?;
+    let json = BASE64_URL_SAFE_NO_PAD
+        .decode(data.trim_end_matches('='))
+        .map_err(|e| format!("Invalid share link payload: {e}"))?;
+    let shared: SharedConfig =
+        serde_json::from_slice(&json).map_err(|e| format!("Invalid share link payload: {e}"))?;
+
+    if shared.version != SHARE_VERSION {
+        return Err(format!(
+            "Unsupported share link version: {}",
+            shared.version
+        ));
+    }
+
+    let scheme = if shared.use_https { "https" } else { "http" };
+    let config = ProxyConfig {
+        id: Uuid::new_v4().to_string(),
+        name: shared.name,
+        listen_address: format!("{scheme}://{}:{}", shared.listen_ip, shared.listen_port),
+        listen_ip: shared.listen_ip,
+        listen_port: shared.listen_port,
+        remote_address: shared.remote_address,
+        remote_host: shared.remote_host,
+        use_https: shared.use_https,
+        headers: shared.headers,
+        ..Default::default()
+    };
+
+    if config.name.trim().is_empty() {
+        return Err("Config name cannot be empty".to_string());
+    }
+    validate_proxy_config(&config).map_err(|e| e.to_string())?;
+
+    Ok(config)
+}
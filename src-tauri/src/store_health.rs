@@ -0,0 +1,80 @@
+use crate::proxy_manager::ProxyConfig;
+use serde::Serialize;
+use serde_json::Value;
+
+/// 单个配置条目的解析结果
+#[derive(Debug, Clone, Serialize)]
+pub struct EntryDiagnosis {
+    pub index: usize,
+    /// 条目中能读取到的 id / name（即使整体解析失败）
+    pub id: Option<String>,
+    pub name: Option<String>,
+    /// 解析失败的原因，成功时为空
+    pub error: Option<String>,
+}
+
+/// 存储中 proxy_configs 的诊断结果
+#[derive(Debug, Clone, Serialize)]
+pub struct StoreDiagnosis {
+    /// 整体能否解析为配置列表
+    pub healthy: bool,
+    pub total_entries: usize,
+    pub valid_entries: usize,
+    /// 不是数组等无法逐条检查时的错误
+    pub error: Option<String>,
+    pub entries: Vec<EntryDiagnosis>,
+    /// 解析失败时返回原始 JSON，便于手动恢复
+    pub raw: Option<Value>,
+}
+
+/// 逐条检查 proxy_configs，定位无法解析的条目
+pub fn diagnose(value: Option<&Value>) -> StoreDiagnosis {
+    let Some(value) = value else {
+        return StoreDiagnosis {
+            healthy: true,
+            total_entries: 0,
+            valid_entries: 0,
+            error: None,
+            entries: Vec::new(),
+            raw: None,
+        };
+    };
+
+    let Some(items) = value.as_array() else {
+        return StoreDiagnosis {
+            healthy: false,
+            total_entries: 0,
+            valid_entries: 0,
+            error: Some("proxy_configs is not an array".to_string()),
+            entries: Vec::new(),
+            raw: Some(value.clone()),
+        };
+    };
+
+    let entries: Vec<EntryDiagnosis> = items
+        .iter()
+        .enumerate()
+        .map(|(index, item)| {
+            let field = |key: &str| item.get(key).and_then(Value::as_str).map(str::to_string);
+            EntryDiagnosis {
+                index,
+                id: field("id"),
+                name: field("name"),
+                error: serde_json::from_value::<ProxyConfig>(item.clone())
+                    .err()
+                    .map(|e| e.to_string()),
+            }
+        })
+        .collect();
+
+    let valid_entries = entries.iter().filter(|e| e.error.is_none()).count();
+    let healthy = valid_entries == entries.len();
+    StoreDiagnosis {
+        healthy,
+        total_entries: entries.len(),
+        valid_entries,
+        error: None,
+        entries,
+        raw: (!healthy).then(|| value.clone()),
+    }
+}
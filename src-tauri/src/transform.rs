@@ -0,0 +1,310 @@
+use axum::body::Body;
+use axum::http::{self, HeaderName, HeaderValue, Request, StatusCode};
+use axum::response::{IntoResponse, Response};
+
+/// 脚本最多允许的规则行数
+pub const MAX_SCRIPT_RULES: usize = 100;
+
+/// 规则的匹配条件
+#[derive(Debug, Clone)]
+enum Condition {
+    Method(http::Method),
+    Path(String),
+    PathPrefix(String),
+    HeaderExists(HeaderName),
+    HeaderEquals(HeaderName, String),
+}
+
+/// 规则的动作
+#[derive(Debug, Clone)]
+enum Action {
+    SetHeader(HeaderName, HeaderValue),
+    RemoveHeader(HeaderName),
+    Respond(StatusCode, String),
+}
+
+#[derive(Debug, Clone)]
+struct Rule {
+    condition: Option<Condition>,
+    action: Action,
+}
+
+/// 解析后的请求变换脚本
+/// 每行一条规则（`#` 开头为注释），形如 `<动作>` 或 `if <条件> then <动作>`：
+/// - 条件：`method GET`、`path /x`、`path-prefix /api`、`header X-Name`、`header X-Name=value`
+/// - 动作：`set-header X-Name value`、`remove-header X-Name`、`respond 403 [响应体]`
+///
+/// 不支持循环，也无法访问文件或网络，执行时间与规则行数成正比，因此无需额外的超时控制
+#[derive(Debug, Clone)]
+pub struct TransformScript {
+    rules: Vec<Rule>,
+}
+
+impl TransformScript {
+    /// 解析脚本，错误信息包含行号
+    pub fn parse(script: &str) -> Result<Self, String> {
+        let mut rules = Vec::new();
+        for (index, line) in script.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if rules.len() >= MAX_SCRIPT_RULES {
+                return Err(format!("Transform script exceeds {MAX_SCRIPT_RULES} rules"));
+            }
+            let rule = parse_rule(line).map_err(|e| format!("Line {}: {e}", index + 1))?;
+            rules.push(rule);
+        }
+        Ok(Self { rules })
+    }
+
+    /// 按顺序执行规则，修改请求头；遇到 respond 时停止并返回该响应
+    pub fn apply(&self, req: &mut Request<Body>) -> Option<Response> {
+        for rule in &self.rules {
+            if rule.condition.as_ref().is_some_and(|c| !c.matches(req)) {
+                continue;
+            }
+            match &rule.action {
+                Action::SetHeader(name, value) => {
+                    req.headers_mut().insert(name.clone(), value.clone());
+                }
+                Action::RemoveHeader(name) => {
+                    req.headers_mut().remove(name);
+                }
+                Action::Respond(status, body) => {
+                    return Some((*status, body.clone()).into_response());
+                }
+            }
+        }
+        None
+    }
+}
+
+impl Condition {
+    fn matches(&self, req: &Request<Body>) -> bool {
+        match self {
+            Condition::Method(method) => req.method() == method,
+            Condition::Path(path) => req.uri().path() == path,
+            Condition::PathPrefix(prefix) => req.uri().path().starts_with(prefix.as_str()),
+            Condition::HeaderExists(name) => req.headers().contains_key(name),
+            Condition::HeaderEquals(name, value) => req
+                .headers()
+                .get_all(name)
+                .iter()
+                .any(|v| v.to_str().is_ok_and(|v| v == value)),
+        }
+    }
+}
+
+fn parse_rule(line: &str) -> Result<Rule, String> {
+    let Some(rest) = line.strip_prefix("if ") else {
+        return Ok(Rule {
+            condition: None,
+            action: parse_action(line)?,
+        });
+    };
+    let (condition, action) = rest
+        .split_once(" then ")
+        .ok_or_else(|| "expected 'if <condition> then <action>'".to_string())?;
+    Ok(Rule {
+        condition: Some(parse_condition(condition.trim())?),
+        action: parse_action(action.trim())?,
+    })
+}
+
+fn parse_condition(text: &str) -> Result<Condition, String> {
+    let (kind, arg) = split_word(text);
+    if arg.is_empty() {
+        return Err(format!("missing argument for condition '{kind}'"));
+    }
+    match kind {
+        "method" => http::Method::from_bytes(arg.to_ascii_uppercase().as_bytes())
+            .map(Condition::Method)
+            .map_err(|_| format!("invalid method: {arg}")),
+        "path" => Ok(Condition::Path(arg.to_string())),
+        "path-prefix" => Ok(Condition::PathPrefix(arg.to_string())),
+        "header" => match arg.split_once('=') {
+            Some((name, value)) => Ok(Condition::HeaderEquals(
+                parse_header_name(name.trim())?,
+                value.trim().to_string(),
+            )),
+            None => Ok(Condition::HeaderExists(parse_header_name(arg)?)),
+        },
+        _ => Err(format!("unknown condition: {kind}")),
+    }
+}
+
+fn parse_action(text: &str) -> Result<Action, String> {
+    let (kind, arg) = split_word(text);
+    match kind {
+        "set-header" => {
+            let (name, value) = split_word(arg);
+            if name.is_empty() {
+                return Err("set-header requires a header name".to_string());
+            }
+            let value = HeaderValue::from_str(value)
+                .map_err(|_| format!("invalid header value: {value}"))?;
+            Ok(Action::SetHeader(parse_header_name(name)?, value))
+        }
+        "remove-header" => Ok(Action::RemoveHeader(parse_header_name(arg)?)),
+        "respond" => {
+            let (status, body) = split_word(arg);
+            let status = status
+                .parse::<u16>()
+                .ok()
+                .and_then(|code| StatusCode::from_u16(code).ok())
+                .filter(|code| code.as_u16() >= 200)
+                .ok_or_else(|| format!("invalid status code: {status}"))?;
+            Ok(Action::Respond(status, body.to_string()))
+        }
+        _ => Err(format!("unknown action: {kind}")),
+    }
+}
+
+fn parse_header_name(name: &str) -> Result<HeaderName, String> {
+    HeaderName::from_bytes(name.as_bytes()).map_err(|_| format!("invalid header name: {name}"))
+}
+
+/// 拆出第一个单词和其余部分（均去除首尾空白）
+fn split_word(text: &str) -> (&str, &str) {
+    let text = text.trim();
+    match text.split_once(char::is_whitespace) {
+        Some((word, rest)) => (word, rest.trim()),
+        None => (text, ""),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(method: &str, uri: &str, headers: &[(&str, &str)]) -> Request<Body> {
+        let mut builder = Request::builder().method(method).uri(uri);
+        for (name, value) in headers {
+            builder = builder.header(*name, *value);
+        }
+        builder.body(Body::empty()).unwrap()
+    }
+
+    /// 对请求执行脚本，返回是否直接响应
+    fn run(script: &str, req: &mut Request<Body>) -> Option<Response> {
+        TransformScript::parse(script).unwrap().apply(req)
+    }
+
+    #[test]
+    fn set_header_without_condition() {
+        let mut req = request("GET", "/", &[]);
+        assert!(run("set-header X-Test hello world", &mut req).is_none());
+        assert_eq!(req.headers()["x-test"], "hello world");
+    }
+
+    #[test]
+    fn remove_header_without_condition() {
+        let mut req = request("GET", "/", &[("X-Secret", "1")]);
+        run("remove-header X-Secret", &mut req);
+        assert!(!req.headers().contains_key("x-secret"));
+    }
+
+    #[test]
+    fn respond_short_circuits_later_rules() {
+        let mut req = request("GET", "/", &[]);
+        let response = run("respond 403 forbidden\nset-header X-After yes", &mut req)
+            .expect("respond should return a response");
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        assert!(!req.headers().contains_key("x-after"));
+    }
+
+    #[test]
+    fn method_condition() {
+        let script = "if method post then set-header X-Matched 1";
+        let mut post = request("POST", "/", &[]);
+        run(script, &mut post);
+        assert!(post.headers().contains_key("x-matched"));
+
+        let mut get = request("GET", "/", &[]);
+        run(script, &mut get);
+        assert!(!get.headers().contains_key("x-matched"));
+    }
+
+    #[test]
+    fn path_condition_matches_exactly() {
+        let script = "if path /admin then respond 403";
+        assert!(run(script, &mut request("GET", "/admin", &[])).is_some());
+        assert!(run(script, &mut request("GET", "/admin/users", &[])).is_none());
+    }
+
+    #[test]
+    fn path_prefix_condition() {
+        let script = "if path-prefix /api then respond 401 unauthorized";
+        assert!(run(script, &mut request("GET", "/api/users", &[])).is_some());
+        assert!(run(script, &mut request("GET", "/web", &[])).is_none());
+    }
+
+    #[test]
+    fn header_exists_condition() {
+        let script = "if header X-Debug then remove-header X-Debug";
+        let mut req = request("GET", "/", &[("X-Debug", "on")]);
+        run(script, &mut req);
+        assert!(!req.headers().contains_key("x-debug"));
+    }
+
+    #[test]
+    fn header_equals_condition() {
+        let script = "if header X-Env = prod then respond 503";
+        assert!(run(script, &mut request("GET", "/", &[("X-Env", "prod")])).is_some());
+        assert!(run(script, &mut request("GET", "/", &[("X-Env", "dev")])).is_none());
+        assert!(run(script, &mut request("GET", "/", &[])).is_none());
+    }
+
+    #[test]
+    fn skips_comments_and_blank_lines() {
+        let script = "# comment\n\n   \nset-header X-Test 1";
+        let mut req = request("GET", "/", &[]);
+        run(script, &mut req);
+        assert!(req.headers().contains_key("x-test"));
+    }
+
+    #[test]
+    fn if_without_then_is_rejected() {
+        let err = TransformScript::parse("if path /x respond 403").unwrap_err();
+        assert!(
+            err.contains("expected 'if <condition> then <action>'"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn errors_include_line_number() {
+        let err = TransformScript::parse("# comment\nset-header X-Ok 1\nexplode now").unwrap_err();
+        assert_eq!(err, "Line 3: unknown action: explode");
+
+        let err = TransformScript::parse("if colour red then respond 403").unwrap_err();
+        assert_eq!(err, "Line 1: unknown condition: colour");
+    }
+
+    #[test]
+    fn rejects_invalid_arguments() {
+        assert!(TransformScript::parse("respond 100").is_err());
+        assert!(TransformScript::parse("respond abc").is_err());
+        assert!(TransformScript::parse("set-header").is_err());
+        assert!(TransformScript::parse("remove-header bad header").is_err());
+        assert!(TransformScript::parse("if method then respond 403").is_err());
+    }
+
+    #[test]
+    fn enforces_rule_limit() {
+        let at_limit = "set-header X-Test 1\n".repeat(MAX_SCRIPT_RULES);
+        assert!(TransformScript::parse(&at_limit).is_ok());
+
+        // 注释不计入规则数
+        let with_comments = format!("# comment\n{at_limit}");
+        assert!(TransformScript::parse(&with_comments).is_ok());
+
+        let over_limit = "set-header X-Test 1\n".repeat(MAX_SCRIPT_RULES + 1);
+        let err = TransformScript::parse(&over_limit).unwrap_err();
+        assert_eq!(
+            err,
+            format!("Transform script exceeds {MAX_SCRIPT_RULES} rules")
+        );
+    }
+}
@@ -0,0 +1,177 @@
+use axum::http::{self, HeaderMap};
+use hyper_util::rt::TokioIo;
+use log::{debug, error, info};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// 转发时每次读取的缓冲区大小
+const COPY_BUFFER_SIZE: usize = 8 * 1024;
+
+/// 判断是否为 WebSocket 升级请求（Connection: upgrade + Upgrade: websocket）
+pub fn is_upgrade_request(headers: &HeaderMap) -> bool {
+    let connection_upgrade = headers
+        .get_all(http::header::CONNECTION)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .any(|token| token.trim().eq_ignore_ascii_case("upgrade"));
+    let upgrade_websocket = headers
+        .get(http::header::UPGRADE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.trim().eq_ignore_ascii_case("websocket"));
+    connection_upgrade && upgrade_websocket
+}
+
+/// 在客户端和上游之间双向转发升级后的连接
+/// log_frames 开启时在 debug 级别记录帧头（方向、opcode、长度），不缓冲也不记录负载
+pub async fn tunnel(
+    client: hyper::upgrade::OnUpgrade,
+    upstream: reqwest::Response,
+    name: String,
+    log_frames: bool,
+) {
+    let client = async { client.await.map_err(|e| e.to_string()) };
+    let upstream = async { upstream.upgrade().await.map_err(|e| e.to_string()) };
+    let (client, upstream) = match tokio::try_join!(client, upstream) {
+        Ok(pair) => pair,
+        Err(e) => {
+            error!("WebSocket upgrade failed for {name}: {e}");
+            return;
+        }
+    };
+    let client = TokioIo::new(client);
+
+    info!("WebSocket tunnel opened for {name}");
+    let (client_read, client_write) = tokio::io::split(client);
+    let (upstream_read, upstream_write) = tokio::io::split(upstream);
+
+    let inspector = |direction| log_frames.then(|| FrameInspector::new(direction, name.clone()));
+    let (sent, received) = tokio::join!(
+        copy_stream(client_read, upstream_write, inspector("client->upstream")),
+        copy_stream(upstream_read, client_write, inspector("upstream->client")),
+    );
+    info!(
+        "WebSocket tunnel closed for {name}: {} bytes sent, {} bytes received",
+        sent.unwrap_or(0),
+        received.unwrap_or(0)
+    );
+}
+
+/// 单向复制，读到的数据先交给检查器解析帧头，再原样写出
+async fn copy_stream<R, W>(
+    mut reader: R,
+    mut writer: W,
+    mut inspector: Option<FrameInspector>,
+) -> std::io::Result<u64>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let mut buf = vec![0u8; COPY_BUFFER_SIZE];
+    let mut total = 0u64;
+    loop {
+        let n = reader.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        if let Some(inspector) = inspector.as_mut() {
+            inspector.feed(&buf[..n]);
+        }
+        writer.write_all(&buf[..n]).await?;
+        total += n as u64;
+    }
+    writer.shutdown().await?;
+    Ok(total)
+}
+
+/// 增量解析 WebSocket 帧头（RFC 6455），帧头可能跨越多次读取
+struct FrameInspector {
+    direction: &'static str,
+    name: String,
+    /// 尚未完整的帧头字节（最长 14 字节）
+    header: Vec<u8>,
+    /// 当前帧剩余未读的负载字节数
+    payload_remaining: u64,
+}
+
+impl FrameInspector {
+    fn new(direction: &'static str, name: String) -> Self {
+        Self {
+            direction,
+            name,
+            header: Vec::with_capacity(14),
+            payload_remaining: 0,
+        }
+    }
+
+    fn feed(&mut self, mut data: &[u8]) {
+        while !data.is_empty() {
+            // 跳过当前帧的负载
+            if self.payload_remaining > 0 {
+                let skip = self.payload_remaining.min(data.len() as u64);
+                self.payload_remaining -= skip;
+                data = &data[skip as usize..];
+                continue;
+            }
+
+            // 累积帧头直到长度足够解析
+            let needed = self.header_len().unwrap_or(2);
+            let take = (needed - self.header.len()).min(data.len());
+            self.header.extend_from_slice(&data[..take]);
+            data = &data[take..];
+
+            if let Some(len) = self.header_len() {
+                if self.header.len() == len {
+                    self.finish_header();
+                }
+            }
+        }
+    }
+
+    /// 根据已读取的前两个字节计算完整帧头长度
+    fn header_len(&self) -> Option<usize> {
+        if self.header.len() < 2 {
+            return None;
+        }
+        let masked = self.header[1] & 0x80 != 0;
+        let extended = match self.header[1] & 0x7f {
+            126 => 2,
+            127 => 8,
+            _ => 0,
+        };
+        Some(2 + extended + if masked { 4 } else { 0 })
+    }
+
+    fn finish_header(&mut self) {
+        let fin = self.header[0] & 0x80 != 0;
+        let opcode = self.header[0] & 0x0f;
+        let masked = self.header[1] & 0x80 != 0;
+        let payload_len = match self.header[1] & 0x7f {
+            126 => u16::from_be_bytes([self.header[2], self.header[3]]) as u64,
+            127 => u64::from_be_bytes(self.header[2..10].try_into().unwrap_or([0; 8])),
+            len => len as u64,
+        };
+
+        debug!(
+            "WebSocket frame {} [{}]: opcode={} ({}) fin={fin} masked={masked} len={payload_len}",
+            self.name,
+            self.direction,
+            opcode,
+            opcode_name(opcode)
+        );
+
+        self.header.clear();
+        self.payload_remaining = payload_len;
+    }
+}
+
+fn opcode_name(opcode: u8) -> &'static str {
+    match opcode {
+        0x0 => "continuation",
+        0x1 => "text",
+        0x2 => "binary",
+        0x8 => "close",
+        0x9 => "ping",
+        0xa => "pong",
+        _ => "reserved",
+    }
+}
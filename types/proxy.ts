@@ -3,9 +3,49 @@
  */
 export interface Header {
   key: string;
+  /** 支持 ${ENV_VAR} 引用环境变量（启动时展开），$${...} 表示字面量 */
   value: string;
+  /** 仅对匹配的请求路径生效（通配符或 re: 正则），未设置时对所有请求生效 */
+  path_pattern?: string | null;
 }
 
+/**
+ * 代理错误响应格式
+ */
+export type ErrorResponseFormat = "text" | "json";
+
+/**
+ * 固定响应
+ */
+export interface StaticResponse {
+  status: number;
+  content_type: string;
+  body: string;
+}
+
+/**
+ * 故障注入配置
+ */
+export interface FaultConfig {
+  latency_ms: number;
+  latency_jitter_ms: number;
+  error_probability: number;
+  error_status: number;
+}
+
+/**
+ * CORS 模式
+ */
+export type CorsMode =
+  | { mode: "permissive" }
+  | { mode: "disabled" }
+  | {
+      mode: "allow_list";
+      origins: string[];
+      methods?: string[];
+      allow_credentials?: boolean;
+    };
+
 /**
  * 代理配置接口
  */
@@ -30,8 +70,168 @@ export interface ProxyConfig {
   headers: Header[];
   /** 是否重写Host请求头 */
   rewrite_host_headers: boolean;
-  /** SOCKS5代理地址 */
+  /** 上游代理地址（http/https/socks5） */
+  upstream_proxy?: string;
+  /** @deprecated 使用 upstream_proxy */
   socks5_proxy?: string;
+  /** 绕过上游代理的主机列表 */
+  proxy_no_proxy?: string[];
+  /** 是否信任客户端传入的 X-Forwarded-* 头 */
+  trust_forwarded_headers?: boolean;
+  /** 是否保留客户端原始Host请求头 */
+  forward_original_host?: boolean;
+  /** 代理自身出错时的响应格式 */
+  error_response_format?: ErrorResponseFormat;
+  /** 是否校验上游TLS证书，未提供时默认校验 */
+  verify_upstream_tls?: boolean;
+  /** 上游自定义CA证书路径 */
+  upstream_ca_path?: string;
+  /** 允许的请求方法（为空表示全部允许） */
+  allowed_methods?: string[];
+  /** 禁止访问的路径模式（通配符，或以 re: 开头的正则） */
+  denied_path_patterns?: string[];
+  /** 启动应用时自动重启上次运行中的代理 */
+  auto_restart?: boolean;
+  /** CORS 模式 */
+  cors?: CorsMode;
+  /** 固定响应（维护模式） */
+  static_response?: StaticResponse | null;
+  /** 故障注入（测试用） */
+  fault_injection?: FaultConfig | null;
+  /** 访问日志文件路径 */
+  access_log_path?: string;
+  /** 自签名证书SAN（DNS名称或IP） */
+  cert_sans?: string[];
+  /** 自签名证书CN */
+  cert_common_name?: string;
+  /** 是否将上游重定向改写为本地地址 */
+  rewrite_redirects?: boolean;
+  /** 每个上游主机的最大空闲连接数 */
+  pool_max_idle_per_host?: number;
+  /** 空闲连接超时（秒） */
+  pool_idle_timeout_seconds?: number;
+  /** 是否记录请求/响应头（debug 级别） */
+  log_headers?: boolean;
+  /** 记录时需要脱敏的请求头 */
+  redacted_headers?: string[];
+  /** 最大并发请求数（按进行中的请求计数，而非 TCP 连接数） */
+  max_connections?: number;
+  /** 转发时添加的查询参数 */
+  query_params_add?: Header[];
+  /** 转发时删除的查询参数 */
+  query_params_remove?: string[];
+  /** 注入的上游 Bearer Token */
+  inject_bearer_token?: string;
+  /** 按 Host 头选择上游，未匹配时使用 remote_address */
+  host_routes?: HostRoute[];
+  /** HTTPS 监听的最低 TLS 版本 */
+  tls_min_version?: '1.2' | '1.3';
+  /** 允许的密码套件名称，为空时使用默认套件 */
+  tls_cipher_suites?: string[];
+  /** 本地健康检查路径（如 /__health），精确匹配时不转发上游 */
+  health_check_path?: string;
+  /** 上游 DNS 覆盖：[主机名, IP]，保留 Host 与 TLS SNI */
+  resolve_override?: [string, string];
+  /** 记录最近请求的元数据以导出 HAR */
+  capture_requests?: boolean;
+  /** 记录的最近请求数上限（默认 100） */
+  capture_buffer_size?: number;
+  /** 上游响应状态码映射：[原状态码, 新状态码] */
+  status_remap?: [number, number][];
+  /** 定期向上游发送 HEAD 请求保持连接预热 */
+  warm_upstream?: boolean;
+  /** 预热请求间隔（秒，默认 30） */
+  warm_interval_seconds?: number;
+  /** Prometheus 指标路径（如 /metrics），该路径不再转发到上游 */
+  prometheus_path?: string;
+  /** 请求追踪日志级别（默认 debug） */
+  trace_level?: TraceLevel;
+  /** 本地静态文件目录 */
+  static_dir?: string;
+  /** 始终转发上游的路径前缀（如 /api），设置后其余路径按 SPA 方式由静态目录处理 */
+  static_api_prefixes?: string[];
+  /** 转发前合并重复斜杠并处理末尾斜杠 */
+  normalize_path?: boolean;
+  /** 末尾斜杠策略（默认 leave） */
+  trailing_slash?: 'leave' | 'add' | 'remove';
+  /** 在 debug 级别记录 WebSocket 帧头（不记录负载） */
+  log_websocket_frames?: boolean;
+  /** 是否启用（默认 true），禁用的配置不会被启动 */
+  enabled?: boolean;
+  /** 列表排序位置（升序） */
+  order?: number;
+  /** 转发前将请求体完整读入内存（可检查/重放） */
+  buffer_request_body?: boolean;
+  /** 缓冲请求体上限（字节，默认 10 MiB），超过返回 413 */
+  max_buffered_body_bytes?: number;
+  /** SNI 证书：[主机名, 证书路径, 私钥路径] */
+  sni_certs?: [string, string, string][];
+  /** 按客户端 Accept-Encoding 压缩未压缩的上游响应 */
+  compress_to_client?: boolean;
+  /** 整体请求期限（秒，含响应体传输），超时返回 504 */
+  request_deadline_seconds?: number;
+  /** 确认允许监听 0.0.0.0 / :: */
+  allow_public_bind?: boolean;
+  /** 保留逐跳头部（调试用，默认移除） */
+  preserve_hop_by_hop_headers?: boolean;
+  /** 注入的上游 Basic 认证：[用户名, 密码] */
+  upstream_basic_auth?: [string, string];
+  /** 转发前移除的请求 Cookie 名称 */
+  strip_request_cookies?: string[];
+  /** 返回客户端前移除的 Set-Cookie 名称 */
+  strip_response_cookies?: string[];
+  /** 转发时覆盖的 User-Agent */
+  override_user_agent?: string;
+  /** 内部跟随上游重定向的最大次数，与 rewrite_redirects 互斥 */
+  follow_redirects?: number;
+  /** 日志 target，可通过 RUST_LOG 单独过滤该代理的日志 */
+  log_target?: string;
+  /** 请求行和请求头的总大小上限（字节），默认 64 KiB，最小 8 KiB */
+  max_request_header_bytes?: number;
+  /** 固定的 X-Forwarded-Proto 值，不再根据监听协议推导 */
+  forwarded_proto_override?: 'http' | 'https';
+  /** 端口被占用时自动尝试后续端口 */
+  auto_increment_port?: boolean;
+  /** 影子上游地址，请求会复制一份发往该地址（需开启 buffer_request_body） */
+  shadow_upstream?: string;
+  /** 分组标签 */
+  tags?: string[];
+  /** 端口被短暂占用时重试绑定 */
+  retry_bind?: boolean;
+  /** 监听套接字启用端口复用（SO_REUSEADDR / SO_REUSEPORT） */
+  reuse_port?: boolean;
+  /** 请求被方法/路径过滤拒绝时返回的自定义响应 */
+  denied_response?: StaticResponse | null;
+  /** 监听端空闲连接超时（秒） */
+  listen_idle_timeout_seconds?: number;
+  /** 允许访问的客户端 IP / CIDR，为空时不限制 */
+  allowed_client_cidrs?: string[];
+  /** 拒绝访问的客户端 IP / CIDR */
+  denied_client_cidrs?: string[];
+  /** 额外监听的端口，与主端口共用同一上游 */
+  additional_ports?: number[];
+  /** 为每个请求附加 X-Request-Id 并回写到响应 */
+  add_request_id?: boolean;
+  /** 上游响应头总大小上限（字节），超出时返回 502 */
+  max_response_header_bytes?: number;
+  /** 移除 Set-Cookie 中指向上游的 Domain，HTTPS 上游经 HTTP 访问时同时调整 Secure / SameSite */
+  rewrite_cookie_domain?: boolean;
+  /** 禁用到上游的连接复用（每个请求新建连接，性能开销较大，仅用于排查问题） */
+  upstream_no_keepalive?: boolean;
+  /** 仅响应这些主机名（精确、*.example.com 或 re: 正则），其他主机返回 421；为空时不限制 */
+  allowed_host_headers?: string[];
+  /** 记录 JSON-RPC 调用的 method 和 id（需开启 buffer_request_body） */
+  jsonrpc_logging?: boolean;
+  /** 客户端和上游连接启用 TCP_NODELAY，默认开启 */
+  tcp_nodelay?: boolean;
+  /** 上游无法连接或超时时返回的 HTML 错误页面路径 */
+  upstream_error_page?: string;
+  /** 转发时添加 X-Original-URI 和 X-Original-Host */
+  add_original_uri_header?: boolean;
+  /** 请求变换脚本：每行一条规则，如 `if path-prefix /admin then respond 403` */
+  transform_script?: string;
+  /** 监听指定网卡的地址（启动时解析，忽略 listen_ip） */
+  listen_interface?: string;
   /** 创建时间戳 */
   created_at: number;
   /** 是否正在运行 */
@@ -54,3 +254,143 @@ export interface ErrorResponse {
   error: string;
   code: string;
 }
+
+/**
+ * 配置校验结果
+ */
+export interface ValidationWarning {
+  field: string;
+  message: string;
+  severity: "error" | "warning";
+}
+
+/**
+ * 代理运行指标
+ */
+export interface ProxyMetrics {
+  requests_total: number;
+  errors_total: number;
+  rejected_total: number;
+  in_flight: number;
+  /** 请求体字节数 */
+  bytes_received: number;
+  /** 响应体字节数 */
+  bytes_sent: number;
+  /** 跨重启累计的计数（上面的字段仅统计本次启动） */
+  lifetime: LifetimeTotals;
+}
+
+/** 跨重启累计的指标 */
+export interface LifetimeTotals {
+  requests_total: number;
+  errors_total: number;
+  bytes_received: number;
+  bytes_sent: number;
+}
+
+/** 请求追踪日志级别 */
+export type TraceLevel = 'off' | 'error' | 'info' | 'debug';
+
+/** Host 路由规则 */
+export interface HostRoute {
+  /** 精确主机名、*.example.com 通配符或 re: 前缀的正则 */
+  host: string;
+  /** 上游地址 */
+  target: string;
+}
+
+/** 运行中代理的信息 */
+export interface ProxyInfo {
+  id: string;
+  name: string;
+  listen_address: string;
+  /** 本次启动时间（RFC 3339） */
+  started_at: string;
+  /** 运行时长（秒） */
+  uptime_seconds: number;
+}
+
+/** 代理的实时运行状态 */
+export interface ProxyStatus {
+  id: string;
+  /** 服务器任务是否仍在运行 */
+  running: boolean;
+  /** 实际监听地址 */
+  bound_address?: string | null;
+  uptime_seconds?: number | null;
+  /** 正在处理的请求数 */
+  in_flight: number;
+  /** 服务器任务异常退出时的错误 */
+  last_error?: string | null;
+}
+
+/** 所有运行中代理的汇总统计 */
+export interface GlobalStats {
+  running_proxies: number;
+  requests_total: number;
+  errors_total: number;
+  rejected_total: number;
+  active_connections: number;
+  bytes_received: number;
+  bytes_sent: number;
+}
+
+/** 单个配置条目的解析结果 */
+export interface EntryDiagnosis {
+  index: number;
+  id?: string | null;
+  name?: string | null;
+  /** 解析失败的原因 */
+  error?: string | null;
+}
+
+/** 存储中配置列表的诊断结果 */
+export interface StoreDiagnosis {
+  healthy: boolean;
+  total_entries: number;
+  valid_entries: number;
+  error?: string | null;
+  entries: EntryDiagnosis[];
+  /** 解析失败时的原始 JSON */
+  raw?: unknown;
+}
+
+/** 后端转发的日志事件（app-log） */
+export interface LogEvent {
+  level: string;
+  target: string;
+  message: string;
+  /** RFC 3339 时间 */
+  timestamp: string;
+}
+
+/**
+ * 代理压测结果（延迟单位为毫秒）
+ */
+export interface BenchResult {
+  url: string;
+  requests: number;
+  concurrency: number;
+  successful: number;
+  /** 连接失败、超时或返回 5xx 的请求数 */
+  failed: number;
+  error_rate: number;
+  duration_ms: number;
+  requests_per_second: number;
+  latency_mean_ms: number;
+  latency_p50_ms: number;
+  latency_p90_ms: number;
+  latency_p99_ms: number;
+  latency_max_ms: number;
+}
+
+/** 上游代理连通性测试结果 */
+export interface ProxyTestResult {
+  success: boolean;
+  /** 通过代理访问目标的耗时（毫秒） */
+  latency_ms?: number | null;
+  status?: number | null;
+  /** INVALID_PROXY_URL / PROXY_UNREACHABLE / PROXY_AUTH_FAILED / TIMEOUT / TARGET_UNREACHABLE */
+  error_code?: string | null;
+  error?: string | null;
+}